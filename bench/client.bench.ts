@@ -0,0 +1,80 @@
+import { Index } from '../src/collection.ts'
+import { Auth, Client } from '../src/common.ts'
+import { EventsStreamTransformer } from '../src/lib/event-stream.ts'
+import { applyFieldPolicies, type FieldPolicies } from '../src/lib/field-policies.ts'
+
+const encoder = new TextEncoder()
+
+const documents = Array.from({ length: 1_000 }, (_, i) => ({
+  id: `${i}`,
+  title: `Document number ${i}`,
+  description: 'Lorem ipsum dolor sit amet, consectetur adipiscing elit. '.repeat(4),
+  body: '<p>Crème brûlée</p>'.repeat(50),
+  price: i * 1.5,
+  tags: ['one', 'two', 'three'],
+}))
+
+const searchResponse = JSON.stringify({
+  count: documents.length,
+  hits: documents.map((document, i) => ({ id: document.id, score: 1 / (i + 1), document })),
+})
+
+const answerStream = encoder.encode(
+  Array.from({ length: 1_000 }, (_, i) => `data: {"type":"answer_token","token":"token ${i} "}\n\n`).join(''),
+)
+
+const policies: FieldPolicies = {
+  body: { action: 'strip' },
+  description: { action: 'truncate', maxLength: 100 },
+  title: { action: 'normalize', form: 'NFC', foldDiacritics: true },
+}
+
+function createClient(fetchImpl: typeof fetch) {
+  return new Client({
+    auth: new Auth({ type: 'apiKey', apiKey: 'key', writerURL: 'http://localhost:8080', readerURL: 'http://localhost:8080' }),
+    fetch: fetchImpl,
+  })
+}
+
+const searchClient = createClient(() => Promise.resolve(new Response(searchResponse)))
+const index = new Index(createClient(() => Promise.resolve(new Response('null'))), 'collection', 'index', { fieldPolicies: policies })
+
+const searchRequest = {
+  path: '/v1/collections/collection/search',
+  method: 'POST',
+  apiKeyPosition: 'query-params',
+  target: 'reader',
+} as const
+
+Deno.bench('Client.request: parse a 1,000 hits search response', async () => {
+  await searchClient.request(searchRequest)
+})
+
+Deno.bench('applyFieldPolicies: 1,000 documents', () => {
+  for (const document of documents) {
+    applyFieldPolicies(document, policies)
+  }
+})
+
+Deno.bench('Index.insertDocumentsBatched: 1,000 documents in batches of 100', async () => {
+  await index.insertDocumentsBatched(documents, { batchSize: 100 })
+})
+
+Deno.bench('Index.insertDocumentsBatched: 1,000 documents with a 64KB byte budget', async () => {
+  await index.insertDocumentsBatched(documents, { batchSize: 1_000, maxBatchBytes: 64 * 1024 })
+})
+
+Deno.bench('EventsStreamTransformer: parse a 1,000 events SSE stream', async () => {
+  const chunks = new ReadableStream<Uint8Array>({
+    start(controller) {
+      for (let i = 0; i < answerStream.length; i += 512) {
+        controller.enqueue(answerStream.subarray(i, i + 512))
+      }
+      controller.close()
+    },
+  })
+
+  for await (const _ of chunks.pipeThrough(new EventsStreamTransformer())) {
+    // drain
+  }
+})
@@ -6,7 +6,17 @@ const outDir = './.npm'
 await emptyDir(outDir)
 
 await build({
-  entryPoints: ['./src/index.ts'],
+  entryPoints: [
+    './src/index.ts',
+    {
+      name: './types',
+      path: './src/lib/types.ts',
+    },
+    {
+      name: './chaos',
+      path: './src/chaos.ts',
+    },
+  ],
   outDir,
   shims: {
     deno: 'dev',
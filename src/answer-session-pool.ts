@@ -0,0 +1,128 @@
+import type { ClientRequestInit } from './common.ts'
+import type { AnswerConfig, OramaCoreStream } from './stream-manager.ts'
+
+import { OramaCapacityError, OramaConfigError, OramaError } from './lib/errors.ts'
+
+export type AnswerSessionPoolConfig = {
+  maxConcurrency: number
+  maxQueueSize?: number
+  // Sessions unused for longer than this (in milliseconds) are dropped from the pool
+  idleTimeout?: number
+}
+
+type PooledSession = {
+  session: OramaCoreStream
+  lastUsed: number
+  generating: boolean
+}
+
+export class AnswerSessionPool {
+  private maxConcurrency: number
+  private maxQueueSize: number
+  private idleTimeout: number
+  private createSession: (sessionID: string) => OramaCoreStream
+
+  private sessions = new Map<string, PooledSession>()
+  private active = 0
+  private queue: (() => void)[] = []
+
+  constructor(config: AnswerSessionPoolConfig, createSession: (sessionID: string) => OramaCoreStream) {
+    if (!Number.isInteger(config.maxConcurrency) || config.maxConcurrency < 1) {
+      throw new OramaConfigError('maxConcurrency must be a positive integer')
+    }
+
+    this.maxConcurrency = config.maxConcurrency
+    this.maxQueueSize = config.maxQueueSize ?? Infinity
+    this.idleTimeout = config.idleTimeout ?? Infinity
+    this.createSession = createSession
+  }
+
+  public getSession(sessionID: string): OramaCoreStream {
+    this.evictIdle()
+
+    let pooled = this.sessions.get(sessionID)
+    if (!pooled) {
+      pooled = { session: this.createSession(sessionID), lastUsed: Date.now(), generating: false }
+      this.sessions.set(sessionID, pooled)
+    }
+
+    pooled.lastUsed = Date.now()
+    return pooled.session
+  }
+
+  public async answer(sessionID: string, data: AnswerConfig, init?: ClientRequestInit): Promise<string> {
+    let result = ''
+    for await (const chunk of this.answerStream(sessionID, data, init)) {
+      result = chunk
+    }
+    return result
+  }
+
+  public async *answerStream(sessionID: string, data: AnswerConfig, init?: ClientRequestInit): AsyncGenerator<string> {
+    const session = this.getSession(sessionID)
+    const pooled = this.sessions.get(sessionID)!
+
+    if (pooled.generating) {
+      throw new OramaError(`Session "${sessionID}" is already generating an answer`)
+    }
+
+    pooled.generating = true
+    try {
+      await this.acquire()
+      try {
+        yield* session.answerStream(data, init)
+      } finally {
+        this.release()
+      }
+    } finally {
+      pooled.generating = false
+      pooled.lastUsed = Date.now()
+    }
+  }
+
+  public evictIdle(): number {
+    const now = Date.now()
+    let evicted = 0
+
+    for (const [sessionID, pooled] of this.sessions) {
+      if (!pooled.generating && now - pooled.lastUsed > this.idleTimeout) {
+        this.sessions.delete(sessionID)
+        evicted++
+      }
+    }
+
+    return evicted
+  }
+
+  public stats(): { sessions: number; active: number; queued: number } {
+    return {
+      sessions: this.sessions.size,
+      active: this.active,
+      queued: this.queue.length,
+    }
+  }
+
+  private async acquire(): Promise<void> {
+    if (this.active < this.maxConcurrency) {
+      this.active++
+      return
+    }
+
+    if (this.queue.length >= this.maxQueueSize) {
+      throw new OramaCapacityError(`Answer session pool queue is full (${this.maxQueueSize} pending requests)`)
+    }
+
+    // The releasing generation hands its slot over directly, so `active` doesn't change here
+    await new Promise<void>((resolve) => this.queue.push(resolve))
+  }
+
+  private release() {
+    const next = this.queue.shift()
+
+    if (next) {
+      next()
+    } else {
+      this.active--
+    }
+  }
+}
@@ -0,0 +1,64 @@
+export type ChaosConfig = {
+  // Extra delay added to every request, picked uniformly between min and max milliseconds
+  latency?: { min: number; max: number }
+  // Each rate is the probability (0 to 1) of the fault being injected on a request
+  timeoutRate?: number
+  networkErrorRate?: number
+  serverErrorRate?: number
+  malformedBodyRate?: number
+  // How long a simulated timeout hangs when the request has no abort signal. Defaults to 30 seconds
+  hangTimeout?: number
+  // Source of randomness, so test runs can be made deterministic
+  random?: () => number
+}
+
+function sleep(ms: number, signal?: AbortSignal | null): Promise<void> {
+  return new Promise((resolve, reject) => {
+    if (signal?.aborted) {
+      return reject(signal.reason)
+    }
+
+    const timeoutID = setTimeout(resolve, ms)
+    signal?.addEventListener('abort', () => {
+      clearTimeout(timeoutID)
+      reject(signal.reason)
+    }, { once: true })
+  })
+}
+
+// Wraps fetch to simulate a misbehaving search backend. Pass the result as `fetch` to any manager to test fallback paths
+export function createChaosFetch(config: ChaosConfig, baseFetch: typeof fetch = fetch): typeof fetch {
+  const random = config.random ?? Math.random
+  const happens = (rate = 0) => random() < rate
+
+  return async (input, init) => {
+    const signal = init?.signal
+
+    if (config.latency) {
+      const { min, max } = config.latency
+      await sleep(min + random() * (max - min), signal)
+    }
+
+    if (happens(config.timeoutRate)) {
+      // Hangs like an unresponsive server, until the caller gives up
+      await sleep(config.hangTimeout ?? 30_000, signal)
+      throw new DOMException('Simulated request timeout', 'TimeoutError')
+    }
+
+    if (happens(config.networkErrorRate)) {
+      throw new TypeError('Simulated network error')
+    }
+
+    if (happens(config.serverErrorRate)) {
+      return new Response('Simulated server error', { status: 503 })
+    }
+
+    const response = await baseFetch(input, init)
+
+    if (happens(config.malformedBodyRate)) {
+      return new Response('{"simulated": "malformed body', { status: response.status, headers: response.headers })
+    }
+
+    return response
+  }
+}
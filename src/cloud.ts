@@ -1,8 +1,14 @@
-import type { AnyObject, SearchResult } from './lib/types.ts'
-import type { Index } from './collection.ts'
+import type { AnyObject, DefaultSearchParams, SearchResult } from './lib/types.ts'
+import type { Index, IndexConfig } from './collection.ts'
 import type { OramaCloudSearchParams } from './lib/types.ts'
+import type { RequestSigningConfig } from './lib/signing.ts'
+import type { KeyProvider } from './common.ts'
+import type { ZeroResultsConfig } from './lib/zero-results.ts'
+import type { TextNormalizationOptions } from './lib/utils.ts'
+import type { ConnectivityListener, ConnectivityState } from './lib/connectivity.ts'
 
 import { CollectionManager } from './collection.ts'
+import { OramaConfigError } from './lib/errors.ts'
 
 export interface ProjectManagerConfig {
   cluster?: {
@@ -11,7 +17,17 @@ export interface ProjectManagerConfig {
   }
   projectId: string
   apiKey: string
+  keyProvider?: KeyProvider
   authJwtURL?: string
+  jwtRefreshMargin?: number
+  onJwtRefreshError?: (error: unknown) => void
+  defaultSearchParams?: DefaultSearchParams
+  zeroResults?: ZeroResultsConfig
+  queryNormalization?: TextNormalizationOptions
+  cjkSegmentation?: { locale?: string }
+  signing?: RequestSigningConfig
+  fetch?: typeof fetch
+  timeout?: number
 }
 
 export class OramaCloud {
@@ -25,6 +41,8 @@ export class OramaCloud {
   public hooks: CollectionManager['hooks']
   public logs: CollectionManager['logs']
   public systemPrompts: CollectionManager['systemPrompts']
+  public segments: CollectionManager['segments']
+  public triggers: CollectionManager['triggers']
   public tools: CollectionManager['tools']
   public shelves: CollectionManager['shelves']
   public pinningRules: CollectionManager['pinningRules']
@@ -43,22 +61,33 @@ export class OramaCloud {
     this.hooks = this.client.hooks
     this.logs = this.client.logs
     this.systemPrompts = this.client.systemPrompts
+    this.segments = this.client.segments
+    this.triggers = this.client.triggers
     this.tools = this.client.tools
     this.shelves = this.client.shelves
     this.pinningRules = this.client.pinningRules
   }
 
+  getConnectivityState(): ConnectivityState {
+    return this.client.getConnectivityState()
+  }
+
+  onConnectivityChange(listener: ConnectivityListener): () => void {
+    return this.client.onConnectivityChange(listener)
+  }
+
   search(params: OramaCloudSearchParams): Promise<SearchResult> {
     const { datasources, ...rest } = params
     return this.client.search({ ...rest, indexes: datasources })
   }
 
-  dataSource(id: string): DataSourceNamespace {
-    const index = this.client.index.set(id)
+  dataSource(id: string, config?: IndexConfig): DataSourceNamespace {
+    const index = this.client.index.set(id, config)
     return new DataSourceNamespace(
       index,
       this.client,
       undefined,
+      config,
     )
   }
 }
@@ -70,6 +99,7 @@ class DataSourceNamespace {
     index: Index,
     private client: CollectionManager,
     private originalRuntimeIndexId?: string,
+    private config?: IndexConfig,
   ) {
     this.index = index
   }
@@ -92,29 +122,31 @@ class DataSourceNamespace {
 
   async createTemporaryIndex(): Promise<DataSourceNamespace> {
     if (this.originalRuntimeIndexId) {
-      throw new Error('Cannot create a temporary index from a temporary index')
+      throw new OramaConfigError('Cannot create a temporary index from a temporary index')
     }
 
     const runtimeIndexID = this.index.getIndexID()
 
     const temp_index_id = await this.index.createTemporaryIndex()
 
-    const index = this.client.index.set(temp_index_id)
+    // The temporary index replaces this one on swap, so its documents must go through the same field policies
+    const index = this.client.index.set(temp_index_id, this.config)
     return new DataSourceNamespace(
       index,
       this.client,
       runtimeIndexID,
+      this.config,
     )
   }
 
   async swap(): Promise<void> {
     if (!this.originalRuntimeIndexId) {
-      throw new Error('Cannot swap a non-temporary index')
+      throw new OramaConfigError('Cannot swap a non-temporary index')
     }
     const tempIndexId = this.index.getIndexID()
 
     if (tempIndexId === this.originalRuntimeIndexId) {
-      throw new Error('Cannot swap the same index')
+      throw new OramaConfigError('Cannot swap the same index')
     }
 
     await this.index.swapTemporaryIndex(
@@ -1,6 +1,7 @@
 import type {
   AnyObject,
   CollectionStats,
+  DefaultSearchParams,
   Hook,
   NLPSearchResult,
   NLPSearchStreamResult,
@@ -17,8 +18,13 @@ import type {
   ExecuteToolsBody,
   ExecuteToolsParsedResponse,
   ExecuteToolsResult,
+  InsertSegmentBody,
+  InsertSegmentResponse,
   InsertSystemPromptBody,
   InsertToolBody,
+  InsertTriggerBody,
+  InsertTriggerResponse,
+  Segment,
   Shelf,
   ShelfInsertObject,
   ShelfWithDocument,
@@ -26,16 +32,39 @@ import type {
   SystemPromptValidationResponse,
   Tool,
   TrainingSetQueryOptimizer,
+  Trigger,
   UpdateToolBody,
   UpdateTriggerResponse,
 } from './index.ts'
 import type { CreateAISessionConfig } from './stream-manager.ts'
-import type { ClientConfig, ClientRequestInit } from './common.ts'
+import type { AnswerSessionPoolConfig } from './answer-session-pool.ts'
+import type { OfflineIndexConfig } from './offline-queue.ts'
+import type { ClientConfig, ClientRequestInit, KeyProvider } from './common.ts'
+import type { RequestSigningConfig } from './lib/signing.ts'
+import type { ConnectivityListener, ConnectivityState } from './lib/connectivity.ts'
 
 import { Profile } from './profile.ts'
 import { OramaCoreStream } from './stream-manager.ts'
+import { AnswerSessionPool } from './answer-session-pool.ts'
+import { OfflineIndex } from './offline-queue.ts'
 import { Auth, Client } from './common.ts'
-import { createRandomString, formatDuration } from './lib/utils.ts'
+import {
+  AdaptiveConcurrency,
+  type AdaptiveConcurrencyOptions,
+  createRandomString,
+  createRangeBuckets,
+  formatDuration,
+  mapConcurrent,
+  normalizeText,
+  type NumberRange,
+  segmentCJK,
+  type TextNormalizationOptions,
+} from './lib/utils.ts'
+import { Namespace } from './lib/namespace.ts'
+import { applyFieldPolicies, type FieldPolicies } from './lib/field-policies.ts'
+import { OramaConfigError, OramaError, OramaParseError } from './lib/errors.ts'
+import { type ImportSource, readCSVRecords, readLines } from './lib/import.ts'
+import { type ZeroResultsConfig, ZeroResultsMonitor } from './lib/zero-results.ts'
 import { parseNLPQueryStream } from 'npm:@orama/oramacore-events-parser@0.0.5'
 import { dedupe } from './index.ts'
 
@@ -98,9 +127,124 @@ export type CreateIndexParams = {
   typeStrategy?: TypeStrategy
 }
 
+export type IndexConfig = {
+  // Applied to every document before it is inserted or upserted, e.g. to drop raw HTML that would bloat the index
+  fieldPolicies?: FieldPolicies
+}
+
+export type CSVImportOptions = BatchOptions & {
+  // Defaults to ','
+  delimiter?: string
+  // Renames columns, e.g. `{ 'Product Name': 'name' }`. Other columns keep their header name
+  columns?: Record<string, string>
+  // Fields (after renaming) converted to numbers. Rows with a non-numeric value are skipped, empty values are left out
+  numericFields?: string[]
+  // Column whose value becomes the document `id`
+  idColumn?: string
+}
+
+export type CSVImportResult = BatchInsertResult & {
+  skipped: {
+    line: number
+    reason: string
+  }[]
+}
+
+export type AuditExpectedDocument = {
+  id: string
+  hash: string
+}
+
+export type AuditReport = {
+  // In the source of truth but not in the collection
+  missing: string[]
+  // In the collection but not in the source of truth
+  extra: string[]
+  // In both, but the hash of the indexed document differs
+  stale: string[]
+}
+
+export type BatchOptions = {
+  // Documents per request. Defaults to 1000
+  batchSize?: number
+  // Batches in flight at once. Defaults to 1, which keeps batches in order.
+  // 'adaptive' raises it while the server keeps up and backs off on errors and latency spikes
+  concurrency?: number | 'adaptive' | AdaptiveConcurrencyOptions
+  // Also caps each batch by the size of its serialized documents, in bytes, to stay under request body limits
+  // when document sizes vary a lot. A document larger than this is sent in a batch of its own
+  maxBatchBytes?: number
+}
+
+export type BatchInsertResult = {
+  batches: number
+  inserted: number
+  failed: {
+    batch: number
+    documents: AnyObject[]
+    error: unknown
+  }[]
+}
+
 const DEFAULT_READER_URL = 'https://collections.orama.com'
 const DEAFULT_JWT_URL = 'https://app.orama.com/api/user/jwt'
 
+const textEncoder = new TextEncoder()
+
+// Groups documents into batches as they come in, by count and optionally by serialized size
+class DocumentBatcher {
+  private batchSize: number
+  private maxBatchBytes?: number
+  private batch: AnyObject[] = []
+  private bytes = 0
+
+  constructor({ batchSize = 1000, maxBatchBytes }: BatchOptions) {
+    if (!Number.isInteger(batchSize) || batchSize < 1) {
+      throw new OramaConfigError('batchSize must be a positive integer')
+    }
+    if (maxBatchBytes !== undefined && !(maxBatchBytes > 0)) {
+      throw new OramaConfigError('maxBatchBytes must be a positive number')
+    }
+
+    this.batchSize = batchSize
+    this.maxBatchBytes = maxBatchBytes
+  }
+
+  // Returns the batches completed by adding the document
+  add(document: AnyObject): AnyObject[][] {
+    const completed: AnyObject[][] = []
+    const bytes = this.maxBatchBytes === undefined ? 0 : textEncoder.encode(JSON.stringify(document)).length
+
+    if (this.batch.length > 0 && this.bytes + bytes > this.maxBatchBytes!) {
+      completed.push(this.take())
+    }
+
+    this.batch.push(document)
+    this.bytes += bytes
+
+    if (this.batch.length >= this.batchSize) {
+      completed.push(this.take())
+    }
+
+    return completed
+  }
+
+  take(): AnyObject[] {
+    const batch = this.batch
+    this.batch = []
+    this.bytes = 0
+    return batch
+  }
+}
+
+// For JSON the server embeds as a string in its responses
+function parseEmbeddedJSON<T>(data: string, field: string): T {
+  try {
+    return JSON.parse(data) as T
+  } catch (error) {
+    throw new OramaParseError(`Unable to parse "${field}" as JSON: ${error}`, data, { cause: error })
+  }
+}
+
 export interface CollectionManagerConfig {
   cluster?: {
     writerURL?: string
@@ -108,13 +252,33 @@ export interface CollectionManagerConfig {
   }
   collectionID: string
   apiKey: string
+  // Supplies a fresh API key when a request is rejected with 401. The request is retried once with the new key
+  keyProvider?: KeyProvider
   authJwtURL?: string
+  // How long before expiry (in milliseconds) the JWT of private API keys is refreshed. Defaults to 30 seconds
+  jwtRefreshMargin?: number
+  onJwtRefreshError?: (error: unknown) => void
+  defaultSearchParams?: DefaultSearchParams
+  zeroResults?: ZeroResultsConfig
+  // Normalizes search terms, to match documents indexed with a 'normalize' field policy
+  queryNormalization?: TextNormalizationOptions
+  // Splits Chinese, Japanese and Korean search terms into words before sending them. `locale` picks the dictionary, e.g. 'ja'
+  cjkSegmentation?: { locale?: string }
+  signing?: RequestSigningConfig
+  fetch?: typeof fetch
+  // Default timeout for every request, in milliseconds. `SearchParams.timeout` overrides it for a single search
+  timeout?: number
+  // Must match the namespace the collection was created in through OramaCoreManager
+  namespace?: string
 }
 
 export class CollectionManager {
   // private url: string
   private collectionID: string
-  private apiKey: string
+  private defaultSearchParams?: DefaultSearchParams
+  private zeroResults?: ZeroResultsMonitor
+  private queryNormalization?: TextNormalizationOptions
+  private cjkSegmentation?: { locale?: string }
   // private writeAPIKey?: string
   // private readAPIKey?: string
   private client: Client
@@ -126,6 +290,8 @@ export class CollectionManager {
   public hooks: HooksNamespace
   public logs: LogsNamespace
   public systemPrompts: SystemPromptsNamespace
+  public segments: SegmentsNamespace
+  public triggers: TriggersNamespace
   public tools: ToolsNamespace
   public identity: IdentityNamespace
   public trainingSets: TrainingSetsNamespace
@@ -135,16 +301,21 @@ export class CollectionManager {
 
   constructor(config: CollectionManagerConfig) {
     let auth: Auth
+    const collectionID = config.namespace ? new Namespace(config.namespace).apply(config.collectionID) : config.collectionID
 
     if (config.apiKey.startsWith('p_')) {
       // OramaCore Cloud Private Api Key (JWT flow)
       auth = new Auth({
         type: 'jwt',
         authJwtURL: config.authJwtURL ?? DEAFULT_JWT_URL,
-        collectionID: config.collectionID,
+        collectionID,
         privateApiKey: config.apiKey,
         readerURL: config.cluster?.readURL ?? DEFAULT_READER_URL,
         writerURL: config.cluster?.writerURL,
+        fetch: config.fetch,
+        keyProvider: config.keyProvider,
+        jwtRefreshMargin: config.jwtRefreshMargin,
+        onJwtRefreshError: config.onJwtRefreshError,
       })
     } else {
       auth = new Auth({
@@ -152,6 +323,7 @@ export class CollectionManager {
         readerURL: config.cluster?.readURL ?? DEFAULT_READER_URL,
         writerURL: config.cluster?.writerURL,
         apiKey: config.apiKey,
+        keyProvider: config.keyProvider,
       })
       this.profile = new Profile({
         endpoint: config.cluster?.readURL ?? DEFAULT_READER_URL,
@@ -160,11 +332,17 @@ export class CollectionManager {
     }
     const commonConfig: ClientConfig = {
       auth,
+      signing: config.signing,
+      fetch: config.fetch,
+      timeout: config.timeout,
     }
 
-    this.collectionID = config.collectionID
+    this.collectionID = collectionID
+    this.defaultSearchParams = config.defaultSearchParams
+    this.zeroResults = config.zeroResults ? new ZeroResultsMonitor(config.zeroResults) : undefined
+    this.queryNormalization = config.queryNormalization
+    this.cjkSegmentation = config.cjkSegmentation
     this.client = new Client(commonConfig)
-    this.apiKey = config.apiKey
 
     // Initialize namespaces
     this.ai = new AINamespace(this.client, this.collectionID, this.profile)
@@ -173,6 +351,8 @@ export class CollectionManager {
     this.hooks = new HooksNamespace(this.client, this.collectionID)
     this.logs = new LogsNamespace(this.client, this.collectionID)
     this.systemPrompts = new SystemPromptsNamespace(this.client, this.collectionID)
+    this.segments = new SegmentsNamespace(this.client, this.collectionID)
+    this.triggers = new TriggersNamespace(this.client, this.collectionID)
     this.tools = new ToolsNamespace(this.client, this.collectionID)
     this.identity = new IdentityNamespace(this.profile)
     this.trainingSets = new TrainingSetsNamespace(this.client, this.collectionID)
@@ -181,9 +361,65 @@ export class CollectionManager {
     this.pinningRules = new PinningRulesNamespace(this.client, this.collectionID)
   }
 
+  public getConnectivityState(): ConnectivityState {
+    return this.client.getConnectivityState()
+  }
+
+  public onConnectivityChange(listener: ConnectivityListener): () => void {
+    return this.client.onConnectivityChange(listener)
+  }
+
+  // Samples documents matching `query` and returns a range facet for `field` with buckets fitted to its values.
+  // Pass the result as `facets` (or merge it into them) on later searches instead of hardcoding ranges.
+  // The first and last buckets are open-ended, so values outside the sample still land in a bucket
+  public async createRangeFacet(
+    field: string,
+    options: { query?: Partial<SearchParams>; sampleSize?: number; buckets?: number } = {},
+    init?: ClientRequestInit,
+  ): Promise<Record<string, { ranges: Partial<NumberRange>[] }>> {
+    const { query, sampleSize = 500, buckets = 5 } = options
+    const { datasourceIDs, indexes, groupBy: _groupBy, timeout, ...restQuery } = query ?? {}
+
+    // Bypasses `search`, so the sample ignores the default search params and doesn't count as a zero-results search
+    const sample = await this.client.request<Omit<SearchResult, 'elapsed'>>({
+      path: `/v1/collections/${this.collectionID}/search`,
+      body: { term: '', ...restQuery, indexes: datasourceIDs || indexes, limit: sampleSize, offset: 0 },
+      method: 'POST',
+      init,
+      apiKeyPosition: 'query-params',
+      target: 'reader',
+      timeout,
+    })
+    const values = sample.hits.map((hit) => Number(hit.document[field])).filter(Number.isFinite)
+
+    const ranges: Partial<NumberRange>[] = createRangeBuckets(values, buckets)
+    if (ranges.length > 0) {
+      delete ranges[0].from
+      delete ranges[ranges.length - 1].to
+    }
+
+    return { [field]: { ranges } }
+  }
+
+  // OramaCore has no multi-search endpoint, so the queries run as concurrent requests. Results keep the order of the queries
+  public msearch<R = AnyObject>(
+    queries: SearchParams[],
+    options: { concurrency?: number } = {},
+    init?: ClientRequestInit,
+  ): Promise<SearchResult<R>[]> {
+    return mapConcurrent(queries, options.concurrency ?? 8, (query) => this.search<R>(query, init))
+  }
+
   public async search<R = AnyObject>(query: SearchParams, init?: ClientRequestInit): Promise<SearchResult<R>> {
     const start = Date.now()
-    const { datasourceIDs, indexes, groupBy, ...restQuery } = query
+    const { datasourceIDs, indexes, groupBy, timeout, ...restQuery } = { ...this.defaultSearchParams, ...query }
+
+    if (this.queryNormalization) {
+      restQuery.term = normalizeText(restQuery.term, this.queryNormalization)
+    }
+    if (this.cjkSegmentation) {
+      restQuery.term = segmentCJK(restQuery.term, this.cjkSegmentation.locale)
+    }
 
     // Extract sortBy from groupBy (client-side only, not sent to backend)
     const groupsSortBy = groupBy?.sortBy
@@ -202,6 +438,7 @@ export class CollectionManager {
       init,
       apiKeyPosition: 'query-params',
       target: 'reader',
+      timeout,
     })
 
     // Sort groups by score of first element if requested
@@ -213,6 +450,8 @@ export class CollectionManager {
       })
     }
 
+    this.zeroResults?.record(query, result.count)
+
     const elapsed = Date.now() - start
 
     return {
@@ -260,12 +499,13 @@ class AINamespace {
     })
 
     if (!response.body) {
-      throw new Error('No response body')
+      throw new OramaParseError(`Response body is empty for NLP query on collection "${this.collectionID}"`, '')
     }
 
     const emitter = parseNLPQueryStream(response.body)
 
     let finished = false
+    let failure: OramaError | undefined
     let results: NLPSearchResult<R>[] = []
 
     emitter.on('search_results', (event: NLPStreamSearchResultsEvent) => {
@@ -273,17 +513,20 @@ class AINamespace {
       finished = true
     })
 
+    // Throwing from the listener would escape as an unhandled error instead of reaching the caller
     emitter.on('error', (e: NLPStreamErrorEvent) => {
-      if (e.is_terminal) {
-        finished = true
-      }
-      throw new Error(e.error)
+      failure = new OramaError(`NLP query failed: ${e.error}`)
+      finished = true
     })
 
     while (!finished) {
       await new Promise((resolve) => setTimeout(resolve, 10))
     }
 
+    if (failure) {
+      throw failure
+    }
+
     return results
   }
 
@@ -314,19 +557,18 @@ class AINamespace {
     })
 
     if (!response.body) {
-      throw new Error('No response body')
+      throw new OramaParseError(`Response body is empty for NLP query on collection "${this.collectionID}"`, '')
     }
 
     let finished = false
+    let failure: OramaError | undefined
     let currentResult: Nullable<NLPSearchStreamResult<R>> = null
 
     const emitter = parseNLPQueryStream(response.body)
 
     emitter.on('error', (e: NLPStreamErrorEvent) => {
-      if (e.is_terminal) {
-        finished = true
-      }
-      throw new Error(e.error)
+      failure = new OramaError(`NLP query failed: ${e.error}`)
+      finished = true
     })
 
     emitter.on('state_changed', (event: NLPStreamStateChangedEvent) => {
@@ -356,6 +598,10 @@ class AINamespace {
       await new Promise((resolve) => setTimeout(resolve, 10))
     }
 
+    if (failure) {
+      throw failure
+    }
+
     // Yield the final search results
     if (currentResult !== null) {
       const deduped = dedupe((currentResult as NLPSearchStreamResult<R>).status)
@@ -372,6 +618,36 @@ class AINamespace {
       ...config,
     })
   }
+
+  public createAISessionPool(poolConfig: AnswerSessionPoolConfig, config?: CreateAISessionConfig): AnswerSessionPool {
+    return new AnswerSessionPool(poolConfig, (sessionID) =>
+      new OramaCoreStream({
+        collectionID: this.collectionID,
+        common: this.client,
+        ...config,
+        sessionID,
+      }))
+  }
+
+  public async resumeAISession(sessionID: string, config: CreateAISessionConfig): Promise<OramaCoreStream> {
+    if (!config.sessionStore) {
+      throw new OramaConfigError('A sessionStore is required to resume an AI session')
+    }
+
+    const session = new OramaCoreStream({
+      collectionID: this.collectionID,
+      common: this.client,
+      sessionID,
+      ...config,
+    })
+
+    const snapshot = await config.sessionStore.load(sessionID)
+    if (snapshot) {
+      session.restore(snapshot)
+    }
+
+    return session
+  }
 }
 
 class CollectionsNamespace {
@@ -393,6 +669,11 @@ class CollectionsNamespace {
     })
   }
 
+  public async count(init?: ClientRequestInit): Promise<number> {
+    const stats = await this.getStats(this.collectionID, init)
+    return stats.document_count
+  }
+
   public regenerateReadAPIKey(init?: ClientRequestInit): Promise<RegenerateReadAPIKeyResponse> {
     return this.client.request<RegenerateReadAPIKeyResponse>({
       path: `/v1/collections/${this.collectionID}/regenerate-read-api-key`,
@@ -413,6 +694,33 @@ class CollectionsNamespace {
       target: 'writer',
     })
   }
+
+  // Compares the collection with a source of truth.
+  // `hashDocument` must hash indexed documents the same way the expected hashes were computed
+  public async audit(
+    expected: Iterable<AuditExpectedDocument> | AsyncIterable<AuditExpectedDocument>,
+    hashDocument: (document: AnyObject) => string | Promise<string>,
+    init?: ClientRequestInit,
+  ): Promise<AuditReport> {
+    const indexed = new Map((await this.getAllDocs(this.collectionID, init)).map((document) => [String(document.id), document]))
+    const report: AuditReport = { missing: [], extra: [], stale: [] }
+
+    for await (const { id, hash } of expected) {
+      const document = indexed.get(id)
+      if (!document) {
+        report.missing.push(id)
+        continue
+      }
+
+      indexed.delete(id)
+      if ((await hashDocument(document)) !== hash) {
+        report.stale.push(id)
+      }
+    }
+
+    report.extra = [...indexed.keys()]
+    return report
+  }
 }
 
 class IndexNamespace {
@@ -447,7 +755,7 @@ class IndexNamespace {
             },
           }
         } else {
-          throw new Error('Invalid enum strategy format. Use "explicit" or "string(N)" where N is a number.')
+          throw new OramaConfigError('Invalid enum strategy format. Use "explicit" or "string(N)" where N is a number.')
         }
       }
     }
@@ -473,11 +781,12 @@ class IndexNamespace {
     })
   }
 
-  public set(id: string): Index {
+  public set(id: string, config?: IndexConfig): Index {
     return new Index(
       this.client,
       this.collectionID,
       id,
+      config,
     )
   }
 }
@@ -764,6 +1073,134 @@ class SystemPromptsNamespace {
   }
 }
 
+class SegmentsNamespace {
+  private client: Client
+  private collectionID: string
+
+  constructor(client: Client, collectionID: string) {
+    this.client = client
+    this.collectionID = collectionID
+  }
+
+  public insert(segment: InsertSegmentBody, init?: ClientRequestInit): Promise<InsertSegmentResponse> {
+    return this.client.request<InsertSegmentResponse>({
+      path: `/v1/collections/${this.collectionID}/segments/insert`,
+      body: segment,
+      method: 'POST',
+      init,
+      apiKeyPosition: 'header',
+      target: 'writer',
+    })
+  }
+
+  public get(id: string, init?: ClientRequestInit): Promise<{ segment: Segment }> {
+    return this.client.request<{ segment: Segment }>({
+      path: `/v1/collections/${this.collectionID}/segments/get`,
+      params: { segment_id: id },
+      method: 'GET',
+      init,
+      apiKeyPosition: 'query-params',
+      target: 'reader',
+    })
+  }
+
+  public getAll(init?: ClientRequestInit): Promise<{ segments: Segment[] }> {
+    return this.client.request<{ segments: Segment[] }>({
+      path: `/v1/collections/${this.collectionID}/segments/all`,
+      method: 'GET',
+      init,
+      apiKeyPosition: 'query-params',
+      target: 'reader',
+    })
+  }
+
+  public delete(id: string, init?: ClientRequestInit): Promise<{ success: boolean }> {
+    return this.client.request<{ success: boolean }>({
+      path: `/v1/collections/${this.collectionID}/segments/delete`,
+      body: { id },
+      method: 'POST',
+      init,
+      apiKeyPosition: 'header',
+      target: 'writer',
+    })
+  }
+
+  public update(segment: Segment, init?: ClientRequestInit): Promise<{ success: boolean }> {
+    return this.client.request<{ success: boolean }>({
+      path: `/v1/collections/${this.collectionID}/segments/update`,
+      body: segment,
+      method: 'POST',
+      init,
+      apiKeyPosition: 'header',
+      target: 'writer',
+    })
+  }
+}
+
+class TriggersNamespace {
+  private client: Client
+  private collectionID: string
+
+  constructor(client: Client, collectionID: string) {
+    this.client = client
+    this.collectionID = collectionID
+  }
+
+  public insert(trigger: InsertTriggerBody, init?: ClientRequestInit): Promise<InsertTriggerResponse> {
+    return this.client.request<InsertTriggerResponse>({
+      path: `/v1/collections/${this.collectionID}/triggers/insert`,
+      body: trigger,
+      method: 'POST',
+      init,
+      apiKeyPosition: 'header',
+      target: 'writer',
+    })
+  }
+
+  public get(id: string, init?: ClientRequestInit): Promise<{ trigger: Trigger }> {
+    return this.client.request<{ trigger: Trigger }>({
+      path: `/v1/collections/${this.collectionID}/triggers/get`,
+      params: { trigger_id: id },
+      method: 'GET',
+      init,
+      apiKeyPosition: 'query-params',
+      target: 'reader',
+    })
+  }
+
+  public getAll(init?: ClientRequestInit): Promise<{ triggers: Trigger[] }> {
+    return this.client.request<{ triggers: Trigger[] }>({
+      path: `/v1/collections/${this.collectionID}/triggers/all`,
+      method: 'GET',
+      init,
+      apiKeyPosition: 'query-params',
+      target: 'reader',
+    })
+  }
+
+  public delete(id: string, init?: ClientRequestInit): Promise<{ success: boolean }> {
+    return this.client.request<{ success: boolean }>({
+      path: `/v1/collections/${this.collectionID}/triggers/delete`,
+      body: { id },
+      method: 'POST',
+      init,
+      apiKeyPosition: 'header',
+      target: 'writer',
+    })
+  }
+
+  public update(trigger: Trigger, init?: ClientRequestInit): Promise<UpdateTriggerResponse> {
+    return this.client.request<UpdateTriggerResponse>({
+      path: `/v1/collections/${this.collectionID}/triggers/update`,
+      body: trigger,
+      method: 'POST',
+      init,
+      apiKeyPosition: 'header',
+      target: 'writer',
+    })
+  }
+}
+
 class ToolsNamespace {
   private client: Client
   private collectionID: string
@@ -786,7 +1223,7 @@ class ToolsNamespace {
         break
       }
       default:
-        throw new Error('Invalid parameters type. Must be string or object')
+        throw new OramaConfigError('Invalid parameters type. Must be string or object')
     }
 
     return this.client.request<void>({
@@ -865,7 +1302,7 @@ class ToolsNamespace {
             return {
               functionResult: {
                 tool_id: result.functionResult.tool_id,
-                result: JSON.parse(result.functionResult.result) as Response,
+                result: parseEmbeddedJSON<Response>(result.functionResult.result, 'functionResult.result'),
               },
             }
           }
@@ -874,7 +1311,7 @@ class ToolsNamespace {
             return {
               functionParameters: {
                 tool_id: result.functionParameters.tool_id,
-                result: JSON.parse(result.functionParameters.result) as Response,
+                result: parseEmbeddedJSON<Response>(result.functionParameters.result, 'functionParameters.result'),
               },
             }
           }
@@ -899,42 +1336,42 @@ class IdentityNamespace {
 
   public get(): string | undefined {
     if (!this.profile) {
-      throw new Error('Profile is not defined')
+      throw new OramaConfigError('Profile is not defined')
     }
     return this.profile.getIdentity()
   }
 
   public getUserId(): string {
     if (!this.profile) {
-      throw new Error('Profile is not defined')
+      throw new OramaConfigError('Profile is not defined')
     }
     return this.profile.getUserId()
   }
 
   public getAlias(): string | undefined {
     if (!this.profile) {
-      throw new Error('Profile is not defined')
+      throw new OramaConfigError('Profile is not defined')
     }
     return this.profile.getAlias()
   }
 
   public async identify(identity: string): Promise<void> {
     if (!this.profile) {
-      throw new Error('Profile is not defined')
+      throw new OramaConfigError('Profile is not defined')
     }
     await this.profile.identify(identity)
   }
 
   public async alias(alias: string): Promise<void> {
     if (!this.profile) {
-      throw new Error('Profile is not defined')
+      throw new OramaConfigError('Profile is not defined')
     }
     await this.profile.alias(alias)
   }
 
   public reset(): void {
     if (!this.profile) {
-      throw new Error('Profile is not defined')
+      throw new OramaConfigError('Profile is not defined')
     }
     this.profile.reset()
   }
@@ -958,7 +1395,7 @@ class TrainingSetsNamespace {
       target: 'reader',
     })
 
-    const trainingSets = response.training_sets && JSON.parse(response.training_sets)
+    const trainingSets = response.training_sets && parseEmbeddedJSON<TrainingSetQueryOptimizer>(response.training_sets, 'training_sets')
     return { training_sets: trainingSets }
   }
 
@@ -1007,19 +1444,48 @@ export class Index {
   private indexID: string
   private collectionID: string
   private oramaInterface: Client
+  private fieldPolicies?: FieldPolicies
   public transaction: Transaction
 
-  constructor(oramaInterface: Client, collectionID: string, indexID: string) {
+  constructor(oramaInterface: Client, collectionID: string, indexID: string, config: IndexConfig = {}) {
     this.indexID = indexID
     this.collectionID = collectionID
     this.oramaInterface = oramaInterface
-    this.transaction = new Transaction(oramaInterface, collectionID, indexID)
+    this.fieldPolicies = config.fieldPolicies
+    this.transaction = new Transaction(oramaInterface, collectionID, indexID, undefined, config.fieldPolicies)
+  }
+
+  private prepareDocuments(documents: AnyObject[]): AnyObject[] {
+    const policies = this.fieldPolicies
+    return policies ? documents.map((document) => applyFieldPolicies(document, policies)) : documents
   }
 
   public getIndexID(): string {
     return this.indexID
   }
 
+  public async count(init?: ClientRequestInit): Promise<number> {
+    const stats = await this.oramaInterface.request<CollectionStats>({
+      path: `/v1/collections/${this.collectionID}/stats`,
+      method: 'GET',
+      init,
+      apiKeyPosition: 'query-params',
+      target: 'reader',
+    })
+
+    // Not a configuration problem: the index may have been deleted since this handle was created
+    const index = stats.indexes_stats.find((index) => index.id === this.indexID)
+    if (!index) {
+      throw new OramaError(`Index "${this.indexID}" doesn't exist in collection "${this.collectionID}"`)
+    }
+
+    return index.document_count
+  }
+
+  public offline(config?: OfflineIndexConfig): OfflineIndex {
+    return new OfflineIndex(this, config)
+  }
+
   public async reindex(init?: ClientRequestInit): Promise<void> {
     await this.oramaInterface.request<void>({
       path: `/v1/collections/${this.collectionID}/indexes/${this.indexID}/reindex`,
@@ -1033,7 +1499,7 @@ export class Index {
   public async insertDocuments<T = AnyObject | AnyObject[]>(documents: T, init?: ClientRequestInit): Promise<void> {
     await this.oramaInterface.request<void>({
       path: `/v1/collections/${this.collectionID}/indexes/${this.indexID}/insert`,
-      body: Array.isArray(documents) ? documents : [documents],
+      body: this.prepareDocuments((Array.isArray(documents) ? documents : [documents]) as AnyObject[]),
       method: 'POST',
       init,
       apiKeyPosition: 'header',
@@ -1041,6 +1507,163 @@ export class Index {
     })
   }
 
+  // Splits large payloads that would time out as a single request. A failed batch doesn't stop the others
+  public async insertDocumentsBatched(
+    documents: AnyObject[],
+    options: BatchOptions = {},
+    init?: ClientRequestInit,
+  ): Promise<BatchInsertResult> {
+    async function* iterate() {
+      yield* documents
+    }
+
+    return this.insertDocumentStream(iterate(), options, init)
+  }
+
+  // Reads newline-delimited JSON from a file or pipe and inserts it batch by batch, so the dataset never has to fit in memory.
+  // Stops with an OramaParseError at the first line that isn't valid JSON
+  public insertDocumentsFromNDJSON(
+    source: ImportSource,
+    options: BatchOptions = {},
+    init?: ClientRequestInit,
+  ): Promise<BatchInsertResult> {
+    async function* documents() {
+      let lineNumber = 0
+
+      for await (const line of readLines(source)) {
+        lineNumber++
+        if (!line.trim()) {
+          continue
+        }
+
+        try {
+          yield JSON.parse(line) as AnyObject
+        } catch (error) {
+          throw new OramaParseError(`Line ${lineNumber} is not valid JSON: ${error}`, line, { cause: error })
+        }
+      }
+    }
+
+    return this.insertDocumentStream(documents(), options, init)
+  }
+
+  // Reads CSV with a header row and inserts it batch by batch. Malformed rows are skipped and listed in `skipped`
+  public async insertDocumentsFromCSV(
+    source: ImportSource,
+    options: CSVImportOptions = {},
+    init?: ClientRequestInit,
+  ): Promise<CSVImportResult> {
+    const { delimiter = ',', columns = {}, numericFields = [], idColumn } = options
+    const skipped: CSVImportResult['skipped'] = []
+
+    async function* documents() {
+      let header: string[] | undefined
+
+      for await (const record of readCSVRecords(source, delimiter)) {
+        if (record.fields.length === 1 && record.fields[0] === '') {
+          continue
+        }
+
+        if (!header) {
+          header = record.fields.map((name) => (name === idColumn ? 'id' : columns[name] ?? name))
+          continue
+        }
+
+        if (record.error) {
+          skipped.push({ line: record.line, reason: record.error })
+          continue
+        }
+
+        if (record.fields.length !== header.length) {
+          skipped.push({ line: record.line, reason: `Expected ${header.length} fields, got ${record.fields.length}` })
+          continue
+        }
+
+        const document: AnyObject = Object.fromEntries(header.map((name, i) => [name, record.fields[i]]))
+        const invalidField = numericFields.find((name) =>
+          document[name] !== undefined && document[name] !== '' && isNaN(Number(document[name]))
+        )
+
+        if (invalidField) {
+          skipped.push({ line: record.line, reason: `Field "${invalidField}" is not a number: "${document[invalidField]}"` })
+          continue
+        }
+
+        for (const name of numericFields) {
+          if (document[name] === '') {
+            delete document[name]
+          } else if (document[name] !== undefined) {
+            document[name] = Number(document[name])
+          }
+        }
+
+        yield document
+      }
+    }
+
+    const result = await this.insertDocumentStream(documents(), options, init)
+    return { ...result, skipped }
+  }
+
+  private async insertDocumentStream(
+    documents: AsyncIterable<AnyObject>,
+    options: BatchOptions,
+    init?: ClientRequestInit,
+  ): Promise<BatchInsertResult> {
+    const { concurrency = 1 } = options
+    const batcher = new DocumentBatcher(options)
+
+    if (typeof concurrency === 'number' && (!Number.isInteger(concurrency) || concurrency < 1)) {
+      throw new OramaConfigError('concurrency must be a positive integer')
+    }
+    const adaptive = typeof concurrency === 'number' ? undefined : new AdaptiveConcurrency(concurrency === 'adaptive' ? {} : concurrency)
+
+    const result: BatchInsertResult = { batches: 0, inserted: 0, failed: [] }
+    const inFlight = new Set<Promise<void>>()
+
+    const flush = async (batch: AnyObject[]) => {
+      if (batch.length === 0) {
+        return
+      }
+
+      const startedAt = Date.now()
+      const sending: Promise<void> = this.sendBatch(batch, result.batches++, result, init)
+        .then((ok) => adaptive?.record(ok, Date.now() - startedAt))
+        .finally(() => inFlight.delete(sending))
+      inFlight.add(sending)
+
+      while (inFlight.size >= (adaptive?.limit ?? concurrency as number)) {
+        await Promise.race(inFlight)
+      }
+    }
+
+    try {
+      for await (const document of documents) {
+        for (const batch of batcher.add(document)) {
+          await flush(batch)
+        }
+      }
+
+      await flush(batcher.take())
+    } finally {
+      await Promise.all(inFlight)
+    }
+
+    result.failed.sort((a, b) => a.batch - b.batch)
+    return result
+  }
+
+  private async sendBatch(batch: AnyObject[], index: number, result: BatchInsertResult, init?: ClientRequestInit): Promise<boolean> {
+    try {
+      await this.insertDocuments(batch, init)
+      result.inserted += batch.length
+      return true
+    } catch (error) {
+      result.failed.push({ batch: index, documents: batch, error })
+      return false
+    }
+  }
+
   public async deleteDocuments(documentIDs: string | string[], init?: ClientRequestInit): Promise<void> {
     await this.oramaInterface.request<void>({
       path: `/v1/collections/${this.collectionID}/indexes/${this.indexID}/delete`,
@@ -1057,7 +1680,7 @@ export class Index {
       path: `/v1/collections/${this.collectionID}/indexes/${this.indexID}/documents/upsert`,
       body: {
         strategy: 'merge',
-        documents: documents as AnyObject[],
+        documents: this.prepareDocuments(documents as AnyObject[]),
       },
       method: 'POST',
       init,
@@ -1110,12 +1733,20 @@ export class Transaction {
   private collectionID: string
   private tempIndexID: string
   private oramaInterface: Client
-
-  constructor(oramaInterface: Client, collectionID: string, indexID: string, tempIndexID: string = createRandomString(16)) {
+  private fieldPolicies?: FieldPolicies
+
+  constructor(
+    oramaInterface: Client,
+    collectionID: string,
+    indexID: string,
+    tempIndexID: string = createRandomString(16),
+    fieldPolicies?: FieldPolicies,
+  ) {
     this.oramaInterface = oramaInterface
     this.collectionID = collectionID
     this.indexID = indexID
     this.tempIndexID = tempIndexID
+    this.fieldPolicies = fieldPolicies
   }
 
   public open(init?: ClientRequestInit): Promise<void> {
@@ -1132,9 +1763,12 @@ export class Transaction {
   }
 
   public insertDocuments(documents: AnyObject | AnyObject[], init?: ClientRequestInit): Promise<void> {
+    const batch = Array.isArray(documents) ? documents : [documents]
+    const policies = this.fieldPolicies
+
     return this.oramaInterface.request<void>({
       path: `/v1/collections/${this.collectionID}/indexes/${this.tempIndexID}/insert`,
-      body: Array.isArray(documents) ? documents : [documents],
+      body: policies ? batch.map((document) => applyFieldPolicies(document, policies)) : batch,
       method: 'POST',
       init,
       apiKeyPosition: 'header',
@@ -1,14 +1,22 @@
+import type { RequestSigningConfig } from './lib/signing.ts'
+import type { ConnectivityListener, ConnectivityState } from './lib/connectivity.ts'
+
 import { EventsStreamTransformer, type SSEEvent } from './lib/event-stream.ts'
+import { createSignatureHeaders } from './lib/signing.ts'
+import { ConnectivityMonitor } from './lib/connectivity.ts'
+import { createHTTPError, OramaConfigError, OramaError, OramaNetworkError, OramaParseError, OramaTimeoutError } from './lib/errors.ts'
 
 type JWTRequestResponse = {
   jwt: string
   writerURL: string
   readerApiKey: string
   readerURL: string
-  expiresIn: number // not used for now
+  expiresIn: number // seconds
 }
 
 export type ApiKeyPosition = 'header' | 'query-params'
+// Returns the current API key. Called again when the server rejects a request with 401, e.g. after a key rotation
+export type KeyProvider = () => string | Promise<string>
 export type ClientRequestInit = Omit<RequestInit, 'method' | 'headers' | 'body'>
 
 type ReadWriteAuth = {
@@ -26,17 +34,61 @@ type JwtAuth = {
   authJwtURL: string
   collectionID: string
   privateApiKey: string
+  // How long before expiry (in milliseconds) the JWT is refreshed. Defaults to 30 seconds
+  jwtRefreshMargin?: number
+  // Called when refreshing the JWT fails. Requests keep using the current token until it actually expires
+  onJwtRefreshError?: (error: unknown) => void
 }
 
 type AuthConfig =
   & ReadWriteAuth
+  & { fetch?: typeof fetch; keyProvider?: KeyProvider }
   & (ApiKeyAuth | JwtAuth)
 
 export class Auth {
-  private config: AuthConfig
+  // Kept in a private field so that API keys don't show up when the client is logged or serialized
+  #config: AuthConfig
+  #jwt?: { token: JWTRequestResponse; expiresAt: number }
+  #jwtRequest?: Promise<JWTRequestResponse>
+  #jwtFetched = false
+  #refreshing?: Promise<boolean>
 
   constructor(config: AuthConfig) {
-    this.config = config
+    this.#config = config
+  }
+
+  // Fetches a fresh key from the key provider. Returns false when there is no provider, so the request can't be retried.
+  // Concurrent 401s share a single call to the key provider
+  public refresh(): Promise<boolean> {
+    if (!this.#refreshing) {
+      this.#refreshing = this.fetchKey().finally(() => {
+        this.#refreshing = undefined
+      })
+    }
+
+    return this.#refreshing
+  }
+
+  private async fetchKey(): Promise<boolean> {
+    // A cached JWT may have been revoked or may have expired early, so a retry with a new one is worth it
+    const hadJwt = this.#jwt !== undefined
+    this.#jwt = undefined
+
+    if (!this.#config.keyProvider) {
+      return hadJwt
+    }
+
+    const key = await this.#config.keyProvider()
+    switch (this.#config.type) {
+      case 'apiKey':
+        this.#config.apiKey = key
+        break
+      case 'jwt':
+        this.#config.privateApiKey = key
+        break
+    }
+
+    return true
   }
 
   public async getRef(
@@ -48,37 +100,31 @@ export class Auth {
   }> {
     let bearer: string
     let baseURL: string
-    switch (this.config.type) {
+    switch (this.#config.type) {
       case 'apiKey': {
-        bearer = this.config.apiKey
-        if (target == 'writer' && !this.config.writerURL) {
-          throw new Error(
+        bearer = this.#config.apiKey
+        if (target == 'writer' && !this.#config.writerURL) {
+          throw new OramaConfigError(
             'Cannot perform a request to a writer without the writerURL. Use `cluster.writerURL` to configure it',
           )
         }
-        if (target == 'reader' && !this.config.readerURL) {
-          throw new Error(
+        if (target == 'reader' && !this.#config.readerURL) {
+          throw new OramaConfigError(
             'Cannot perform a request to a writer without the writerURL. Use `cluster.readerURL` to configure it',
           )
         }
-        baseURL = target == 'writer' ? this.config.writerURL! : this.config.readerURL!
+        baseURL = target == 'writer' ? this.#config.writerURL! : this.#config.readerURL!
         break
       }
       case 'jwt': {
-        const ret = await getJwtToken(
-          this.config.authJwtURL,
-          this.config.collectionID,
-          this.config.privateApiKey,
-          'write',
-          init,
-        )
+        const ret = await this.getJwt(this.#config, init)
         // NB: This allow us to support at *client side* a way invocation to reader with private api key!!
         if (target == 'reader') {
-          baseURL = ret.readerURL ?? this.config.readerURL
+          baseURL = ret.readerURL ?? this.#config.readerURL
           bearer = ret.readerApiKey
         } else {
           bearer = ret.jwt
-          baseURL = ret.writerURL ?? this.config.writerURL
+          baseURL = ret.writerURL ?? this.#config.writerURL
         }
         break
       }
@@ -89,6 +135,48 @@ export class Auth {
       baseURL,
     }
   }
+
+  private async getJwt(config: AuthConfig & JwtAuth, init?: ClientRequestInit): Promise<JWTRequestResponse> {
+    const cached = this.#jwt
+    if (cached && Date.now() < cached.expiresAt - (config.jwtRefreshMargin ?? 30_000)) {
+      return cached.token
+    }
+
+    // Concurrent requests share a single refresh. It runs without the caller's signal, as aborting one request
+    // must not fail the others waiting on it
+    if (!this.#jwtRequest) {
+      const requestedAt = Date.now()
+      const { signal: _signal, ...sharedInit } = init ?? {}
+      this.#jwtRequest = getJwtToken(config.authJwtURL, config.collectionID, config.privateApiKey, 'write', sharedInit, config.fetch)
+        .then((token) => {
+          // expiresIn is relative, so measuring it against the local clock is immune to skew with the auth server
+          if (token.expiresIn > 0) {
+            this.#jwt = { token, expiresAt: requestedAt + token.expiresIn * 1000 }
+          }
+          this.#jwtFetched = true
+          return token
+        })
+        .catch((error) => {
+          // Without a previous token this is the first fetch, not a refresh, and the error reaches the caller anyway
+          if (this.#jwtFetched) {
+            config.onJwtRefreshError?.(error)
+          }
+          throw error
+        })
+        .finally(() => {
+          this.#jwtRequest = undefined
+        })
+    }
+
+    try {
+      return await raceSignal(this.#jwtRequest, init?.signal)
+    } catch (error) {
+      if (!init?.signal?.aborted && cached && Date.now() < cached.expiresAt) {
+        return cached.token
+      }
+      throw error
+    }
+  }
 }
 
 export type ClientRequest = {
@@ -99,42 +187,69 @@ export type ClientRequest = {
   params?: Record<string, string>
   init?: ClientRequestInit
   apiKeyPosition: ApiKeyPosition
+  timeout?: number
 }
 
 export interface ClientConfig {
   auth: Auth
+  signing?: RequestSigningConfig
+  // Custom fetch implementation, e.g. undici's fetch with a dispatcher bound to a specific local address
+  fetch?: typeof fetch
+  // Default timeout for every request, in milliseconds. Requests with their own timeout (e.g. `SearchParams.timeout`) override it
+  timeout?: number
 }
 
 export class Client {
-  private config: ClientConfig
+  #config: ClientConfig
+  private connectivity = new ConnectivityMonitor()
 
   constructor(config: ClientConfig) {
-    this.config = config
+    this.#config = config
+  }
+
+  public getConnectivityState(): ConnectivityState {
+    return this.connectivity.getState()
+  }
+
+  public onConnectivityChange(listener: ConnectivityListener): () => void {
+    return this.connectivity.subscribe(listener)
   }
 
   public async request<Output>(req: ClientRequest): Promise<Output> {
-    const response = await this.getResponse(req)
+    // The timeout keeps running until the body is read, as a server can stall after sending the headers
+    const { response, release } = await this.send(req)
+
+    try {
+      if (!response.ok) {
+        let text
+        try {
+          text = await response.text()
+        } catch (e) {
+          text = `Unable to got response body ${e}`
+        }
+        throw createHTTPError(
+          `Request to "${req.path}?${new URLSearchParams(req.params ?? {}).toString()}" failed with status ${response.status}: ${text}`,
+          { status: response.status, body: text, path: req.path },
+        )
+      }
+
+      const text = await response.text()
 
-    if (!response.ok) {
-      let text
       try {
-        text = await response.text()
+        return JSON.parse(text) as Output
       } catch (e) {
-        text = `Unable to got response body ${e}`
+        throw new OramaParseError(`Unable to parse response from "${req.path}" as JSON: ${e}`, text, { cause: e })
       }
-      throw new Error(
-        `Request to "${req.path}?${new URLSearchParams(req.params ?? {}).toString()}" failed with status ${response.status}: ${text}`,
-      )
+    } finally {
+      release()
     }
-
-    return response.json() as Promise<Output>
   }
 
   public async requestStream(req: ClientRequest): Promise<ReadableStream<SSEEvent>> {
     const response = await this.getResponse(req)
 
     if (response.body === null) {
-      throw new Error(`Response body is null for "${req.path}"`)
+      throw new OramaError(`Response body is null for "${req.path}"`)
     }
 
     return response.body?.pipeThrough(new EventsStreamTransformer())
@@ -142,12 +257,12 @@ export class Client {
 
   public async eventSource(req: ClientRequest): Promise<EventSource> {
     if (req.apiKeyPosition !== 'query-params') {
-      throw new Error(
+      throw new OramaConfigError(
         `EventSource only supports apiKeyPosition as 'query-params', but got ${req.apiKeyPosition}`,
       )
     }
     if (req.method !== 'GET') {
-      throw new Error(
+      throw new OramaConfigError(
         `EventSource only supports GET requests, but got ${req.method}`,
       )
     }
@@ -155,7 +270,7 @@ export class Client {
     const {
       baseURL,
       bearer,
-    } = await this.config.auth.getRef(req.target, req.init)
+    } = await this.#config.auth.getRef(req.target, req.init)
     const remoteURL = new URL(req.path, baseURL)
 
     req.params = req.params ?? {}
@@ -167,19 +282,42 @@ export class Client {
     return new EventSource(remoteURL)
   }
 
-  async getResponse({
-    method,
-    path,
-    body,
-    params,
-    apiKeyPosition,
-    init,
-    target,
-  }: ClientRequest): Promise<Response> {
+  // For streams, which can outlive the timeout: it only covers getting the response headers.
+  // The caller's signal stays linked to the request, so it can still cancel the body
+  async getResponse(req: ClientRequest): Promise<Response> {
+    const { response, stopTimeout, release } = await this.send(req)
+    stopTimeout()
+
+    if (!response.ok) {
+      let text
+      try {
+        text = await response.text()
+      } catch (e) {
+        text = `Unable to got response body ${e}`
+      } finally {
+        release()
+      }
+      throw createHTTPError(
+        `Stream request to "${req.path}" failed with status ${response.status}: ${text}`,
+        { status: response.status, body: text, path: req.path },
+      )
+    }
+
+    return response
+  }
+
+  private async send(
+    req: ClientRequest,
+    isRetry = false,
+  ): Promise<{ response: Response; stopTimeout: () => void; release: () => void }> {
+    let { params } = req
+    const { method, path, body, apiKeyPosition, init, target } = req
+    const timeout = req.timeout ?? this.#config.timeout
+
     const {
       baseURL,
       bearer,
-    } = await this.config.auth.getRef(target, init)
+    } = await this.#config.auth.getRef(target, init)
 
     const remoteURL = new URL(path, baseURL)
     const headers = new Headers()
@@ -207,21 +345,116 @@ export class Client {
       remoteURL.search = new URLSearchParams(params).toString()
     }
 
-    const response = await fetch(remoteURL, requestObject)
-
-    if (response.status === 401) {
-      throw new Error(
-        `Unauthorized: are you using the correct Api Key?`,
+    if (this.#config.signing) {
+      const signatureHeaders = await createSignatureHeaders(
+        this.#config.signing,
+        method,
+        remoteURL,
+        (requestObject.body as string | undefined) ?? '',
       )
+      for (const [name, value] of Object.entries(signatureHeaders)) {
+        headers.append(name, value)
+      }
     }
-    if (response.status === 400) {
-      const errorText = await response.text()
-      throw new Error(
-        `Bad Request: ${errorText} (path: ${remoteURL.toString()})`,
+
+    let timeoutID: ReturnType<typeof setTimeout> | undefined
+    let unlinkSignal = () => {}
+    if (timeout !== undefined) {
+      const controller = new AbortController()
+      const signal = init?.signal
+
+      if (signal?.aborted) {
+        controller.abort(signal.reason)
+      }
+      if (signal) {
+        const forwardAbort = () => controller.abort(signal.reason)
+        signal.addEventListener('abort', forwardAbort, { once: true })
+        unlinkSignal = () => signal.removeEventListener('abort', forwardAbort)
+      }
+      timeoutID = setTimeout(
+        () => controller.abort(new OramaTimeoutError(`Request to "${path}" timed out after ${timeout}ms`)),
+        timeout,
       )
+      requestObject.signal = controller.signal
     }
-    return response
+
+    const stopTimeout = () => clearTimeout(timeoutID)
+    // Long-lived caller signals would otherwise collect a listener per request
+    const release = () => {
+      stopTimeout()
+      unlinkSignal()
+    }
+
+    let response: Response
+    try {
+      response = await (this.#config.fetch ?? fetch)(remoteURL, requestObject)
+    } catch (error) {
+      release()
+
+      // Custom fetch implementations may time out on their own, with a DOMException
+      const timedOut = error instanceof OramaTimeoutError || (error instanceof Error && error.name === 'TimeoutError')
+
+      // Aborts requested by the caller say nothing about the server
+      if (error instanceof TypeError || timedOut) {
+        this.connectivity.record('network-error')
+      }
+      // fetch rejects with a TypeError when the server can't be reached at all
+      if (error instanceof TypeError) {
+        throw new OramaNetworkError(`Unable to reach "${remoteURL.origin}" for "${path}": ${error.message}`, { cause: error })
+      }
+      if (timedOut && !(error instanceof OramaTimeoutError)) {
+        throw new OramaTimeoutError(`Request to "${path}" timed out`, { cause: error })
+      }
+      throw error
+    }
+
+    this.connectivity.record(response.status >= 500 ? 'server-error' : 'success')
+
+    try {
+      if (response.status === 401) {
+        if (!isRetry && (await this.#config.auth.refresh())) {
+          // Frees the connection, which would otherwise stay busy until the unread body is garbage collected
+          await response.body?.cancel()
+          release()
+          return this.send(req, true)
+        }
+        throw createHTTPError(
+          `Unauthorized: are you using the correct Api Key?`,
+          { status: 401, body: await response.text(), path },
+        )
+      }
+      if (response.status === 400) {
+        const errorText = await response.text()
+        throw createHTTPError(
+          `Bad Request: ${errorText} (path: ${remoteURL.toString()})`,
+          { status: 400, body: errorText, path },
+        )
+      }
+    } catch (error) {
+      release()
+      throw error
+    }
+
+    return { response, stopTimeout, release }
+  }
+}
+
+// Stops waiting for `promise` once `signal` aborts, without cancelling the work behind it
+function raceSignal<T>(promise: Promise<T>, signal?: AbortSignal | null): Promise<T> {
+  if (!signal) {
+    return promise
   }
+
+  return new Promise<T>((resolve, reject) => {
+    const onAbort = () => reject(signal.reason)
+    if (signal.aborted) {
+      onAbort()
+    } else {
+      signal.addEventListener('abort', onAbort, { once: true })
+    }
+    // Still settles through `promise`, so a shared request that fails after every caller aborted isn't left unhandled
+    promise.then(resolve, reject).finally(() => signal.removeEventListener('abort', onAbort))
+  })
 }
 
 async function getJwtToken(
@@ -230,13 +463,14 @@ async function getJwtToken(
   privateApiKey: string,
   scope: 'write',
   init?: ClientRequestInit,
+  fetchImpl: typeof fetch = fetch,
 ): Promise<JWTRequestResponse> {
   const payload = {
     collectionId,
     privateApiKey,
     scope,
   }
-  const request = await fetch(authJwtUrl, {
+  const request = await fetchImpl(authJwtUrl, {
     method: 'POST',
     headers: {
       'Content-Type': 'application/json',
@@ -246,7 +480,12 @@ async function getJwtToken(
   })
 
   if (!request.ok) {
-    throw new Error(`JWT request to ${request.url} failed with status ${request.status}: ${await request.text()}`)
+    const body = await request.text()
+    throw createHTTPError(`JWT request to ${request.url} failed with status ${request.status}: ${body}`, {
+      status: request.status,
+      body,
+      path: authJwtUrl,
+    })
   }
 
   return request.json() as Promise<JWTRequestResponse>
@@ -0,0 +1,267 @@
+import type { EmbeddingsModel, Hook, InsertSystemPromptBody, Language, SystemPrompt } from './lib/types.ts'
+import type { CreateIndexParams } from './collection.ts'
+import type { NewCollectionResponse, OramaCoreManager } from './manager.ts'
+import type { RequestSigningConfig } from './lib/signing.ts'
+
+import { CollectionManager } from './collection.ts'
+import { OramaConfigError, OramaParseError } from './lib/errors.ts'
+
+export type DeclarativeCollection = {
+  id: string
+  description?: string
+  language?: Language
+  embeddingsModel?: EmbeddingsModel
+  readAPIKey?: string
+  // Required to reconcile indexes, hooks and system prompts of collections that already exist
+  writeAPIKey?: string
+  indexes?: (CreateIndexParams & { id: string })[]
+  hooks?: Partial<Record<Hook, string>>
+  systemPrompts?: (InsertSystemPromptBody & { id: string })[]
+}
+
+export type DeclarativeConfig = {
+  collections: DeclarativeCollection[]
+}
+
+export type ConfigChange =
+  | { type: 'create_collection'; collectionID: string }
+  | { type: 'create_index'; collectionID: string; index: CreateIndexParams & { id: string } }
+  | { type: 'set_hook'; collectionID: string; hook: Hook; code: string }
+  | { type: 'insert_system_prompt'; collectionID: string; systemPrompt: InsertSystemPromptBody & { id: string } }
+  | { type: 'update_system_prompt'; collectionID: string; systemPrompt: SystemPrompt }
+
+export type ConnectionConfig = {
+  url: string
+  signing?: RequestSigningConfig
+  fetch?: typeof fetch
+  namespace?: string
+}
+
+export type ReconcileStatus = {
+  upToDate: boolean
+  applied: ConfigChange[]
+  pending: ConfigChange[]
+  // Collections created by this run, by ID. Holds the API keys generated for collections configured without them,
+  // which can't be recovered from the server afterwards
+  created: Record<string, NewCollectionResponse>
+  failed?: {
+    // Missing when the current state of the server couldn't be read, so no change was attempted
+    change?: ConfigChange
+    error: unknown
+  }
+}
+
+// Accepts a parsed config or its JSON source, and checks the shape before anything is sent to the server
+export function parseConfig(config: DeclarativeConfig | string): DeclarativeConfig {
+  let desired: unknown = config
+
+  if (typeof config === 'string') {
+    try {
+      desired = JSON.parse(config)
+    } catch (error) {
+      throw new OramaParseError(`Unable to parse declarative config as JSON: ${error}`, config, { cause: error })
+    }
+  }
+
+  const collections = (desired as Partial<DeclarativeConfig> | null)?.collections
+  if (!Array.isArray(collections)) {
+    throw new OramaConfigError('Declarative config must have a "collections" array')
+  }
+
+  collections.forEach((collection, i) => {
+    if (typeof collection?.id !== 'string' || collection.id === '') {
+      throw new OramaConfigError(`Collection at position ${i} of the declarative config needs an "id"`)
+    }
+  })
+
+  return desired as DeclarativeConfig
+}
+
+function hasSubResources(collection: DeclarativeCollection): boolean {
+  return Boolean(collection.indexes?.length || Object.keys(collection.hooks ?? {}).length || collection.systemPrompts?.length)
+}
+
+function collectionManagerFor(connection: ConnectionConfig, collectionID: string, writeAPIKey: string): CollectionManager {
+  return new CollectionManager({
+    cluster: {
+      readURL: connection.url,
+      writerURL: connection.url,
+    },
+    collectionID,
+    apiKey: writeAPIKey,
+    signing: connection.signing,
+    fetch: connection.fetch,
+    namespace: connection.namespace,
+  })
+}
+
+export async function diffConfig(
+  manager: OramaCoreManager,
+  connection: ConnectionConfig,
+  config: DeclarativeConfig,
+): Promise<ConfigChange[]> {
+  const existing = new Map((await manager.collection.list()).map((collection) => [collection.id, collection]))
+  const changes: ConfigChange[] = []
+
+  for (const desired of config.collections) {
+    const current = existing.get(desired.id)
+
+    if (!current) {
+      changes.push({ type: 'create_collection', collectionID: desired.id })
+      changes.push(...(desired.indexes ?? []).map((index): ConfigChange => ({ type: 'create_index', collectionID: desired.id, index })))
+      changes.push(
+        ...Object.entries(desired.hooks ?? {}).map(([hook, code]): ConfigChange => ({
+          type: 'set_hook',
+          collectionID: desired.id,
+          hook: hook as Hook,
+          code,
+        })),
+      )
+      changes.push(
+        ...(desired.systemPrompts ?? []).map((systemPrompt): ConfigChange => ({
+          type: 'insert_system_prompt',
+          collectionID: desired.id,
+          systemPrompt,
+        })),
+      )
+      continue
+    }
+
+    if (!hasSubResources(desired)) {
+      continue
+    }
+
+    if (!desired.writeAPIKey) {
+      throw new OramaConfigError(`Collection "${desired.id}" needs a writeAPIKey to reconcile its indexes, hooks and system prompts`)
+    }
+
+    const collection = collectionManagerFor(connection, desired.id, desired.writeAPIKey)
+    const currentIndexes = new Set(current.indexes.map((index) => index.id))
+
+    for (const index of desired.indexes ?? []) {
+      if (!currentIndexes.has(index.id)) {
+        changes.push({ type: 'create_index', collectionID: desired.id, index })
+      }
+    }
+
+    if (desired.hooks) {
+      const currentHooks = await collection.hooks.list()
+
+      for (const [hook, code] of Object.entries(desired.hooks)) {
+        if (currentHooks[hook as Hook] !== code) {
+          changes.push({ type: 'set_hook', collectionID: desired.id, hook: hook as Hook, code })
+        }
+      }
+    }
+
+    if (desired.systemPrompts) {
+      const { system_prompts } = await collection.systemPrompts.getAll()
+      const currentPrompts = new Map(system_prompts.map((prompt) => [prompt.id, prompt]))
+
+      for (const systemPrompt of desired.systemPrompts) {
+        const currentPrompt = currentPrompts.get(systemPrompt.id)
+
+        if (!currentPrompt) {
+          changes.push({ type: 'insert_system_prompt', collectionID: desired.id, systemPrompt })
+        } else if (
+          currentPrompt.name !== systemPrompt.name ||
+          currentPrompt.prompt !== systemPrompt.prompt ||
+          currentPrompt.usage_mode !== systemPrompt.usage_mode
+        ) {
+          changes.push({ type: 'update_system_prompt', collectionID: desired.id, systemPrompt })
+        }
+      }
+    }
+  }
+
+  return changes
+}
+
+export async function executeChanges(
+  manager: OramaCoreManager,
+  connection: ConnectionConfig,
+  config: DeclarativeConfig,
+  changes: ConfigChange[],
+): Promise<ReconcileStatus> {
+  const desired = new Map(config.collections.map((collection) => [collection.id, collection]))
+  // Keys generated while creating collections are needed by the changes that follow
+  const writeAPIKeys = new Map(config.collections.map((collection) => [collection.id, collection.writeAPIKey]))
+
+  const collectionFor = (collectionID: string) => {
+    const writeAPIKey = writeAPIKeys.get(collectionID)
+    if (!writeAPIKey) {
+      throw new OramaConfigError(`Collection "${collectionID}" needs a writeAPIKey to reconcile its indexes, hooks and system prompts`)
+    }
+    return collectionManagerFor(connection, collectionID, writeAPIKey)
+  }
+
+  const applied: ConfigChange[] = []
+  const created: Record<string, NewCollectionResponse> = {}
+
+  for (const [i, change] of changes.entries()) {
+    try {
+      switch (change.type) {
+        case 'create_collection': {
+          const { id, description, language, embeddingsModel, readAPIKey, writeAPIKey } = desired.get(change.collectionID)!
+          const collection = await manager.collection.create({ id, description, language, embeddingsModel, readAPIKey, writeAPIKey })
+          created[change.collectionID] = collection
+          writeAPIKeys.set(change.collectionID, collection.writeAPIKey)
+          break
+        }
+        case 'create_index':
+          await collectionFor(change.collectionID).index.create(change.index)
+          break
+        case 'set_hook':
+          await collectionFor(change.collectionID).hooks.insert({ name: change.hook, code: change.code })
+          break
+        case 'insert_system_prompt':
+          await collectionFor(change.collectionID).systemPrompts.insert(change.systemPrompt)
+          break
+        case 'update_system_prompt':
+          await collectionFor(change.collectionID).systemPrompts.update(change.systemPrompt)
+          break
+      }
+    } catch (error) {
+      // Later changes may depend on this one, so stop here and let the next reconcile pick up from the current state
+      return {
+        upToDate: false,
+        applied,
+        pending: changes.slice(i),
+        created,
+        failed: { change, error },
+      }
+    }
+
+    applied.push(change)
+  }
+
+  return {
+    upToDate: true,
+    applied,
+    pending: [],
+    created,
+  }
+}
+
+export function formatConfigPlan(changes: ConfigChange[]): string {
+  if (changes.length === 0) {
+    return 'No changes. The server matches the configuration.'
+  }
+
+  const lines = changes.map((change) => {
+    switch (change.type) {
+      case 'create_collection':
+        return `+ create collection "${change.collectionID}"`
+      case 'create_index':
+        return `+ create index "${change.index.id}" in collection "${change.collectionID}"`
+      case 'set_hook':
+        return `~ set hook "${change.hook}" in collection "${change.collectionID}"`
+      case 'insert_system_prompt':
+        return `+ insert system prompt "${change.systemPrompt.id}" in collection "${change.collectionID}"`
+      case 'update_system_prompt':
+        return `~ update system prompt "${change.systemPrompt.id}" in collection "${change.collectionID}"`
+    }
+  })
+
+  return [...lines, '', `Plan: ${changes.length} change${changes.length === 1 ? '' : 's'}.`].join('\n')
+}
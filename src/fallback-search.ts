@@ -0,0 +1,111 @@
+import type { AnyObject, SearchParams, SearchResult } from './lib/types.ts'
+import type { ClientRequestInit } from './common.ts'
+import type { CollectionManager } from './collection.ts'
+
+export type FallbackStrategy<R = AnyObject> =
+  // The last successful result for the same query
+  | 'cache'
+  // The same query in plain fulltext mode, which doesn't depend on embeddings or LLMs. Skipped when the query is already fulltext
+  | 'fulltext'
+  | { name: string; search: (query: SearchParams, error: unknown) => Promise<SearchResult<R>> }
+
+export type FallbackSearcherConfig<R = AnyObject> = {
+  // Timeout for the primary search, in milliseconds
+  timeout?: number
+  // Tried in order until one succeeds
+  fallbacks: FallbackStrategy<R>[]
+  // Number of queries kept for the 'cache' strategy. Defaults to 100
+  cacheSize?: number
+}
+
+export type FallbackSearchResult<R = AnyObject> = {
+  result: SearchResult<R>
+  // 'primary', 'cache', 'fulltext' or the name of a custom strategy
+  servedBy: string
+  // Errors of the paths tried before the one that served the results
+  errors: unknown[]
+}
+
+export class FallbackSearcher<R = AnyObject> {
+  private collection: CollectionManager
+  private config: FallbackSearcherConfig<R>
+  private cache = new Map<string, SearchResult<R>>()
+
+  constructor(collection: CollectionManager, config: FallbackSearcherConfig<R>) {
+    this.collection = collection
+    this.config = config
+  }
+
+  public async search(query: SearchParams, init?: ClientRequestInit): Promise<FallbackSearchResult<R>> {
+    const errors: unknown[] = []
+
+    try {
+      const result = await this.collection.search<R>({ timeout: this.config.timeout, ...query }, init)
+      this.remember(query, result)
+      return { result, servedBy: 'primary', errors }
+    } catch (error) {
+      // The caller gave up on the search, so there is nobody left to serve a fallback to
+      if (isAbortError(error)) {
+        throw error
+      }
+      errors.push(error)
+    }
+
+    for (const strategy of this.config.fallbacks) {
+      if (strategy === 'fulltext' && query.mode === 'fulltext') {
+        continue
+      }
+
+      try {
+        const result = await this.runStrategy(strategy, query, errors[errors.length - 1], init)
+        if (result) {
+          return { result, servedBy: typeof strategy === 'string' ? strategy : strategy.name, errors }
+        }
+      } catch (error) {
+        if (isAbortError(error)) {
+          throw error
+        }
+        errors.push(error)
+      }
+    }
+
+    throw new AggregateError(errors, 'The primary search and every fallback failed')
+  }
+
+  private runStrategy(
+    strategy: FallbackStrategy<R>,
+    query: SearchParams,
+    error: unknown,
+    init?: ClientRequestInit,
+  ): Promise<SearchResult<R> | undefined> {
+    switch (strategy) {
+      case 'cache':
+        return Promise.resolve(this.cache.get(cacheKey(query)))
+      case 'fulltext':
+        return this.collection.search<R>({ ...query, mode: 'fulltext' }, init)
+      default:
+        return strategy.search(query, error)
+    }
+  }
+
+  private remember(query: SearchParams, result: SearchResult<R>) {
+    const key = cacheKey(query)
+
+    // Re-inserting moves the key to the end, so the first key is always the least recently stored
+    this.cache.delete(key)
+    this.cache.set(key, result)
+
+    if (this.cache.size > (this.config.cacheSize ?? 100)) {
+      this.cache.delete(this.cache.keys().next().value!)
+    }
+  }
+}
+
+function isAbortError(error: unknown): boolean {
+  return error instanceof Error && error.name === 'AbortError'
+}
+
+function cacheKey(query: SearchParams): string {
+  const { timeout: _timeout, ...rest } = query
+  return JSON.stringify(rest)
+}
@@ -1,11 +1,60 @@
 export { STATES_STEPS as answerSessionSteps } from 'npm:@orama/oramacore-events-parser@0.0.5'
-export type { AnswerConfig, AnswerSessionConfig, Interaction } from './stream-manager.ts'
+export type {
+  AnswerConfig,
+  AnswerSessionBranch,
+  AnswerSessionConfig,
+  ContentFilter,
+  ContentFilterContext,
+  Interaction,
+} from './stream-manager.ts'
 
 export { OramaCoreStream as AnswerSession } from './stream-manager.ts'
+export type { AnswerSessionSnapshot, KeyValueStorage, SessionStore } from './session-store.ts'
+export { KeyValueSessionStore, LocalStorageSessionStore, MemorySessionStore } from './session-store.ts'
+export type { AnswerSessionPoolConfig } from './answer-session-pool.ts'
+export { AnswerSessionPool } from './answer-session-pool.ts'
+export type { OfflineIndexConfig, OfflineQueueStorage, QueuedWrite } from './offline-queue.ts'
+export { LocalStorageOfflineQueueStorage, MemoryOfflineQueueStorage, OfflineIndex } from './offline-queue.ts'
+export type { FallbackSearcherConfig, FallbackSearchResult, FallbackStrategy } from './fallback-search.ts'
+export { FallbackSearcher } from './fallback-search.ts'
+export type { SearchSessionConfig } from './search-session.ts'
+export { SearchSession } from './search-session.ts'
 
 export * from './lib/types.ts'
-export { createRandomString } from './lib/utils.ts'
+export type { KeyProvider } from './common.ts'
+export {
+  OramaCapacityError,
+  OramaConfigError,
+  OramaError,
+  OramaHTTPError,
+  OramaNetworkError,
+  OramaParseError,
+  OramaRequestError,
+  OramaServerError,
+  OramaTimeoutError,
+} from './lib/errors.ts'
+export type { RequestSigningConfig } from './lib/signing.ts'
+export type { ConnectivityListener, ConnectivityState } from './lib/connectivity.ts'
+export { Namespace } from './lib/namespace.ts'
+export { LANGUAGE_ISO_CODES, languageFromISOCode, languageToISOCode } from './lib/language.ts'
+export type { FieldPolicies, FieldPolicy } from './lib/field-policies.ts'
+export type { ImportSource } from './lib/import.ts'
+export type { ZeroResultsConfig } from './lib/zero-results.ts'
+export type { AdaptiveConcurrencyOptions, NumberRange, TextNormalizationOptions } from './lib/utils.ts'
+export {
+  createRandomString,
+  createRangeBuckets,
+  createULID,
+  createUUID,
+  isULID,
+  isUUID,
+  normalizeText,
+  renderPromptTemplate,
+  segmentCJK,
+} from './lib/utils.ts'
 export * from './manager.ts'
+export type { ConfigChange, DeclarativeCollection, DeclarativeConfig, ReconcileStatus } from './declarative.ts'
+export { formatConfigPlan } from './declarative.ts'
 export * from './collection.ts'
 export * from './cloud.ts'
 
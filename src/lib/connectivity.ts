@@ -0,0 +1,56 @@
+export type ConnectivityState = 'online' | 'degraded' | 'offline'
+
+export type RequestOutcome = 'success' | 'server-error' | 'network-error'
+
+export type ConnectivityListener = (state: ConnectivityState, previous: ConnectivityState) => void
+
+const DEGRADED_FAILURE_RATIO = 0.2
+
+export class ConnectivityMonitor {
+  private outcomes: RequestOutcome[] = []
+  private state: ConnectivityState = 'online'
+  private listeners = new Set<ConnectivityListener>()
+  private windowSize: number
+
+  constructor(windowSize = 20) {
+    this.windowSize = windowSize
+  }
+
+  public getState(): ConnectivityState {
+    return this.state
+  }
+
+  public subscribe(listener: ConnectivityListener): () => void {
+    this.listeners.add(listener)
+    return () => this.listeners.delete(listener)
+  }
+
+  public record(outcome: RequestOutcome) {
+    this.outcomes.push(outcome)
+    if (this.outcomes.length > this.windowSize) {
+      this.outcomes.shift()
+    }
+
+    const failures = this.outcomes.filter((o) => o !== 'success').length
+    let state: ConnectivityState = 'online'
+
+    if (outcome === 'network-error') {
+      state = 'offline'
+    } else if (failures / this.outcomes.length >= DEGRADED_FAILURE_RATIO) {
+      state = 'degraded'
+    }
+
+    if (state !== this.state) {
+      const previous = this.state
+      this.state = state
+      // Listeners run inside the request that changed the state, so one that throws must not fail that request
+      this.listeners.forEach((listener) => {
+        try {
+          listener(state, previous)
+        } catch (error) {
+          console.warn('Connectivity listener failed with error:', error)
+        }
+      })
+    }
+  }
+}
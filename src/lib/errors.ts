@@ -0,0 +1,101 @@
+// Base class for every error thrown by the client, so callers can tell them apart from their own errors.
+// `retryable` tells whether sending the same request again may succeed without changing anything
+export class OramaError extends Error {
+  public readonly retryable: boolean = false
+
+  constructor(message: string, options?: ErrorOptions) {
+    super(message, options)
+    this.name = 'OramaError'
+  }
+}
+
+// The client is missing something it needs before a request can be sent, e.g. a writer URL
+export class OramaConfigError extends OramaError {
+  constructor(message: string, options?: ErrorOptions) {
+    super(message, options)
+    this.name = 'OramaConfigError'
+  }
+}
+
+// The server was reached and answered with a non-2xx status. Thrown as OramaRequestError or OramaServerError
+export class OramaHTTPError extends OramaError {
+  public readonly status: number
+  public readonly body: string
+  public readonly path: string
+
+  constructor(message: string, details: { status: number; body: string; path: string }, options?: ErrorOptions) {
+    super(message, options)
+    this.name = 'OramaHTTPError'
+    this.status = details.status
+    this.body = details.body
+    this.path = details.path
+  }
+}
+
+// 4xx: the request itself was rejected (bad input, wrong key, missing collection). Only rate limiting and
+// request timeouts (429 and 408) are worth retrying, after a delay
+export class OramaRequestError extends OramaHTTPError {
+  public override readonly retryable: boolean
+
+  constructor(message: string, details: { status: number; body: string; path: string }, options?: ErrorOptions) {
+    super(message, details, options)
+    this.name = 'OramaRequestError'
+    this.retryable = details.status === 408 || details.status === 429
+  }
+}
+
+// 5xx: the server failed to handle a valid request. Retrying reads is safe. A retried write may have been applied
+// already, so only retry writes that are idempotent (upserts, deletes)
+export class OramaServerError extends OramaHTTPError {
+  public override readonly retryable = true
+
+  constructor(message: string, details: { status: number; body: string; path: string }, options?: ErrorOptions) {
+    super(message, details, options)
+    this.name = 'OramaServerError'
+  }
+}
+
+export function createHTTPError(message: string, details: { status: number; body: string; path: string }): OramaHTTPError {
+  return details.status >= 500 ? new OramaServerError(message, details) : new OramaRequestError(message, details)
+}
+
+// The server couldn't be reached at all, so the request never got there. The original fetch error is available as `cause`
+export class OramaNetworkError extends OramaError {
+  public override readonly retryable = true
+
+  constructor(message: string, options?: ErrorOptions) {
+    super(message, options)
+    this.name = 'OramaNetworkError'
+  }
+}
+
+// The request didn't complete within its timeout. The server may still have processed it, so only retry idempotent requests
+export class OramaTimeoutError extends OramaError {
+  public override readonly retryable = true
+
+  constructor(message: string, options?: ErrorOptions) {
+    super(message, options)
+    this.name = 'OramaTimeoutError'
+  }
+}
+
+// A client-side queue is full, so the request was rejected before being sent. Retry once pending work has completed
+export class OramaCapacityError extends OramaError {
+  public override readonly retryable = true
+
+  constructor(message: string, options?: ErrorOptions) {
+    super(message, options)
+    this.name = 'OramaCapacityError'
+  }
+}
+
+// The server answered with a body that isn't valid JSON
+export class OramaParseError extends OramaError {
+  public readonly body: string
+
+  constructor(message: string, body: string, options?: ErrorOptions) {
+    super(message, options)
+    this.name = 'OramaParseError'
+    this.body = body
+  }
+}
@@ -0,0 +1,46 @@
+import type { AnyObject } from './types.ts'
+
+import { normalizeText, type TextNormalizationOptions } from './utils.ts'
+
+export type FieldPolicy =
+  // Removes the field. When `sourceURLField` is set, its value is kept as `source_url` so the original content can still be found
+  | { action: 'strip'; sourceURLField?: string }
+  // Cuts string values down to `maxLength` characters. Non-string values are left untouched
+  | { action: 'truncate'; maxLength: number }
+  // Applies Unicode normalization and optional diacritics folding to string values. Use the same options for queries
+  | ({ action: 'normalize' } & TextNormalizationOptions)
+
+// Keyed by top-level document field
+export type FieldPolicies = Record<string, FieldPolicy>
+
+// Returns a copy of the document with the policies applied. The original document is not modified
+export function applyFieldPolicies<T extends AnyObject>(document: T, policies: FieldPolicies): T {
+  const result: AnyObject = { ...document }
+
+  for (const [field, policy] of Object.entries(policies)) {
+    if (!(field in result)) {
+      continue
+    }
+
+    switch (policy.action) {
+      case 'strip':
+        if (policy.sourceURLField && result[policy.sourceURLField] != null) {
+          result.source_url = result[policy.sourceURLField]
+        }
+        delete result[field]
+        break
+      case 'truncate':
+        if (typeof result[field] === 'string') {
+          result[field] = Array.from(result[field] as string).slice(0, policy.maxLength).join('')
+        }
+        break
+      case 'normalize':
+        if (typeof result[field] === 'string') {
+          result[field] = normalizeText(result[field] as string, policy)
+        }
+        break
+    }
+  }
+
+  return result as T
+}
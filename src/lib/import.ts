@@ -0,0 +1,105 @@
+export type ImportSource = ReadableStream<Uint8Array> | AsyncIterable<Uint8Array | string>
+
+async function* chunksOf(source: ImportSource): AsyncGenerator<Uint8Array | string> {
+  if (!('getReader' in source)) {
+    return yield* source
+  }
+
+  const reader = source.getReader()
+  try {
+    while (true) {
+      const { done, value } = await reader.read()
+      if (done) {
+        return
+      }
+      yield value
+    }
+  } finally {
+    reader.releaseLock()
+  }
+}
+
+// Splits a byte or text stream into lines without buffering more than the current line. Handles both \n and \r\n
+export async function* readLines(source: ImportSource): AsyncGenerator<string> {
+  const decoder = new TextDecoder()
+  let buffered = ''
+
+  for await (const chunk of chunksOf(source)) {
+    buffered += typeof chunk === 'string' ? chunk : decoder.decode(chunk, { stream: true })
+
+    const lines = buffered.split('\n')
+    buffered = lines.pop()!
+
+    for (const line of lines) {
+      yield line.endsWith('\r') ? line.slice(0, -1) : line
+    }
+  }
+
+  buffered += decoder.decode()
+  if (buffered) {
+    yield buffered.endsWith('\r') ? buffered.slice(0, -1) : buffered
+  }
+}
+
+export type CSVRecord = {
+  line: number
+  fields: string[]
+  error?: string
+}
+
+// RFC 4180 parser that keeps its state across chunks, so quoted fields may contain delimiters, quotes ("") and line breaks
+export async function* readCSVRecords(source: ImportSource, delimiter = ','): AsyncGenerator<CSVRecord> {
+  const decoder = new TextDecoder()
+  let fields: string[] = []
+  let field = ''
+  let inQuotes = false
+  let closingQuote = false
+  let line = 1
+  let recordLine = 1
+
+  for await (const chunk of chunksOf(source)) {
+    const text = typeof chunk === 'string' ? chunk : decoder.decode(chunk, { stream: true })
+
+    for (const char of text) {
+      if (inQuotes) {
+        if (closingQuote) {
+          closingQuote = false
+          if (char === '"') {
+            field += '"'
+            continue
+          }
+          inQuotes = false
+        } else {
+          if (char === '"') {
+            closingQuote = true
+          } else {
+            field += char
+            if (char === '\n') line++
+          }
+          continue
+        }
+      }
+
+      if (char === '"' && field === '') {
+        inQuotes = true
+      } else if (char === delimiter) {
+        fields.push(field)
+        field = ''
+      } else if (char === '\n') {
+        fields.push(field)
+        yield { line: recordLine, fields }
+        fields = []
+        field = ''
+        recordLine = ++line
+      } else if (char !== '\r') {
+        field += char
+      }
+    }
+  }
+
+  if (inQuotes && !closingQuote) {
+    yield { line: recordLine, fields: [...fields, field], error: 'Unterminated quoted field' }
+  } else if (field !== '' || fields.length > 0) {
+    yield { line: recordLine, fields: [...fields, field] }
+  }
+}
@@ -0,0 +1,54 @@
+import type { Language } from './types.ts'
+
+export const LANGUAGE_ISO_CODES: Record<Language, string> = {
+  arabic: 'ar',
+  bulgarian: 'bg',
+  chinese: 'zh',
+  danish: 'da',
+  dutch: 'nl',
+  german: 'de',
+  greek: 'el',
+  english: 'en',
+  estonian: 'et',
+  spanish: 'es',
+  finnish: 'fi',
+  french: 'fr',
+  irish: 'ga',
+  hindi: 'hi',
+  hungarian: 'hu',
+  armenian: 'hy',
+  indonesian: 'id',
+  italian: 'it',
+  japanese: 'ja',
+  korean: 'ko',
+  lituanian: 'lt',
+  nepali: 'ne',
+  norwegian: 'no',
+  portuguese: 'pt',
+  romanian: 'ro',
+  russian: 'ru',
+  sanskrit: 'sa',
+  slovenian: 'sl',
+  serbian: 'sr',
+  swedish: 'sv',
+  tamil: 'ta',
+  turkish: 'tr',
+  ukrainian: 'uk',
+}
+
+const LANGUAGES_BY_ISO_CODE: Record<string, Language> = {
+  ...Object.fromEntries(Object.entries(LANGUAGE_ISO_CODES).map(([language, code]) => [code, language as Language])),
+  // Bokmål and Nynorsk are both handled as Norwegian
+  nb: 'norwegian',
+  nn: 'norwegian',
+}
+
+export function languageToISOCode(language: Language): string {
+  return LANGUAGE_ISO_CODES[language]
+}
+
+// Accepts bare ISO 639-1 codes as well as locale tags like `pt-BR` or `en_US`. Returns undefined for unsupported languages
+export function languageFromISOCode(code: string): Language | undefined {
+  const [primary] = code.trim().toLowerCase().split(/[-_]/)
+  return Object.hasOwn(LANGUAGES_BY_ISO_CODE, primary) ? LANGUAGES_BY_ISO_CODE[primary] : undefined
+}
@@ -0,0 +1,30 @@
+import { OramaConfigError } from './errors.ts'
+
+export const NAMESPACE_SEPARATOR = '__'
+
+// Prefixes collection IDs per environment or tenant, e.g. `products` becomes `prod__products` in the `prod` namespace
+export class Namespace {
+  public readonly name: string
+  private prefix: string
+
+  constructor(name: string) {
+    if (!name) {
+      throw new OramaConfigError('Namespace name cannot be empty')
+    }
+
+    this.name = name
+    this.prefix = `${name}${NAMESPACE_SEPARATOR}`
+  }
+
+  public apply(collectionID: string): string {
+    return this.prefix + collectionID
+  }
+
+  public contains(collectionID: string): boolean {
+    return collectionID.startsWith(this.prefix)
+  }
+
+  public strip(collectionID: string): string {
+    return this.contains(collectionID) ? collectionID.slice(this.prefix.length) : collectionID
+  }
+}
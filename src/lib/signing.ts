@@ -0,0 +1,35 @@
+export type RequestSigningConfig = {
+  secret: string
+  signatureHeader?: string
+  timestampHeader?: string
+  digestHeader?: string
+}
+
+const encoder = new TextEncoder()
+
+function toHex(buffer: ArrayBuffer): string {
+  return Array.from(new Uint8Array(buffer), (byte) => byte.toString(16).padStart(2, '0')).join('')
+}
+
+// Signs `timestamp\nMETHOD\n/path?query\nsha256(body)` with HMAC-SHA256 and returns the headers to attach to the request
+export async function createSignatureHeaders(
+  config: RequestSigningConfig,
+  method: string,
+  url: URL,
+  body: string,
+): Promise<Record<string, string>> {
+  const timestamp = Math.floor(Date.now() / 1000).toString()
+  const digest = toHex(await crypto.subtle.digest('SHA-256', encoder.encode(body)))
+  const payload = [timestamp, method.toUpperCase(), url.pathname + url.search, digest].join('\n')
+
+  const key = await crypto.subtle.importKey('raw', encoder.encode(config.secret), { name: 'HMAC', hash: 'SHA-256' }, false, [
+    'sign',
+  ])
+  const signature = toHex(await crypto.subtle.sign('HMAC', key, encoder.encode(payload)))
+
+  return {
+    [config.timestampHeader ?? 'X-Orama-Timestamp']: timestamp,
+    [config.digestHeader ?? 'X-Orama-Content-SHA256']: digest,
+    [config.signatureHeader ?? 'X-Orama-Signature']: signature,
+  }
+}
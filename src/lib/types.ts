@@ -80,8 +80,12 @@ export type SearchParams = {
   tolerance?: number
   userID?: string
   groupBy?: { properties: string[]; max_results?: number; sortBy?: GroupsSortBy }
+  // Client-side request timeout in milliseconds, not sent to the server
+  timeout?: number
 }
 
+export type DefaultSearchParams = Partial<Omit<SearchParams, 'term'>>
+
 export type OramaCloudSearchParams = Omit<SearchParams, 'indexes'> & { datasources?: string[] }
 
 export type Hit<T = AnyObject> = {
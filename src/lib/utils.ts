@@ -1,19 +1,210 @@
 import type { Maybe } from './types.ts'
 
+import { OramaConfigError } from './errors.ts'
+
 export const LOCAL_STORAGE_USER_ID_KEY = '___orama_anonymous_user_id'
 export const LOCAL_STORAGE_SERVER_SIDE_SESSION_KEY = '___orama_server_side_session'
 
-export function createRandomString(length: number): string {
-  const characters = 'ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789_-$'
+export const DEFAULT_RANDOM_STRING_CHARACTERS = 'ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789_-$'
+
+export function createRandomString(length: number, characters: string = DEFAULT_RANDOM_STRING_CHARACTERS): string {
+  if (characters.length === 0) {
+    throw new OramaConfigError('Cannot create a random string from an empty character set')
+  }
+
+  // Uses the Web Crypto API, so generated strings are safe to use as API keys
+  const values = crypto.getRandomValues(new Uint32Array(length))
   let result = ''
 
   for (let i = 0; i < length; i++) {
-    result += characters.charAt(Math.floor(Math.random() * characters.length))
+    result += characters.charAt(values[i] % characters.length)
   }
 
   return result
 }
 
+const CROCKFORD_BASE32 = '0123456789ABCDEFGHJKMNPQRSTVWXYZ'
+const UUID_REGEX = /^[0-9a-f]{8}-[0-9a-f]{4}-[1-8][0-9a-f]{3}-[89ab][0-9a-f]{3}-[0-9a-f]{12}$/i
+const ULID_REGEX = /^[0-7][0-9A-HJKMNP-TV-Z]{25}$/
+
+export function createUUID(): string {
+  return crypto.randomUUID()
+}
+
+export function isUUID(id: string): boolean {
+  return UUID_REGEX.test(id)
+}
+
+// ULIDs sort lexicographically by creation time, which keeps ingestion order stable across pipelines
+export function createULID(timestamp: number = Date.now()): string {
+  if (!Number.isInteger(timestamp) || timestamp < 0 || timestamp > 2 ** 48 - 1) {
+    throw new OramaConfigError(`Invalid ULID timestamp: ${timestamp}`)
+  }
+
+  let time = ''
+  for (let i = 0; i < 10; i++) {
+    time = CROCKFORD_BASE32.charAt(timestamp % 32) + time
+    timestamp = Math.floor(timestamp / 32)
+  }
+
+  return time + createRandomString(16, CROCKFORD_BASE32)
+}
+
+export function isULID(id: string): boolean {
+  return ULID_REGEX.test(id)
+}
+
+export function renderPromptTemplate(template: string, variables: Record<string, string | number>): string {
+  return template.replace(/\{\{\s*([\w.-]+)\s*\}\}/g, (_, name: string) => {
+    if (!Object.hasOwn(variables, name)) {
+      throw new OramaConfigError(`Missing value for prompt variable "${name}"`)
+    }
+    return String(variables[name])
+  })
+}
+
+// Supports `*` (any sequence of characters) and `?` (a single character)
+export function globToRegExp(glob: string): RegExp {
+  const pattern = glob
+    .split('')
+    .map((char) => {
+      if (char === '*') return '.*'
+      if (char === '?') return '.'
+      return char.replace(/[.+^${}()|[\]\\]/g, '\\$&')
+    })
+    .join('')
+
+  return new RegExp(`^${pattern}$`)
+}
+
+export type TextNormalizationOptions = {
+  // Unicode normalization form. Defaults to NFC
+  form?: 'NFC' | 'NFD' | 'NFKC' | 'NFKD'
+  // Strips accents from Latin, Greek and Cyrillic letters, so that "café" becomes "cafe". Marks in other scripts are kept
+  foldDiacritics?: boolean
+}
+
+export function normalizeText(text: string, options: TextNormalizationOptions = {}): string {
+  const form = options.form ?? 'NFC'
+
+  if (!options.foldDiacritics) {
+    return text.normalize(form)
+  }
+
+  return text
+    .normalize('NFD')
+    .replace(/([\p{Script=Latin}\p{Script=Greek}\p{Script=Cyrillic}])\p{M}+/gu, '$1')
+    .normalize(form)
+}
+
+const CJK_PATTERN = /[\p{Script=Han}\p{Script=Hiragana}\p{Script=Katakana}\p{Script=Hangul}]/u
+
+// Chinese and Japanese don't separate words with spaces, so queries are split into words with the runtime's
+// dictionary-based segmenter. Text without CJK characters, or runtimes without Intl.Segmenter, are left untouched
+export function segmentCJK(text: string, locale?: string): string {
+  if (!CJK_PATTERN.test(text) || typeof Intl.Segmenter !== 'function') {
+    return text
+  }
+
+  const segmenter = new Intl.Segmenter(locale, { granularity: 'word' })
+  return Array.from(segmenter.segment(text))
+    .filter((segment) => segment.isWordLike)
+    .map((segment) => segment.segment)
+    .join(' ')
+}
+
+export type NumberRange = { from: number; to: number }
+
+// Rounds to two significant digits, so bucket boundaries read like 20 or 1500 rather than 19.87 or 1497.2
+function roundBoundary(value: number): number {
+  return Number(value.toPrecision(2))
+}
+
+// Splits values into up to `count` ranges holding roughly the same number of values each
+export function createRangeBuckets(values: number[], count = 5): NumberRange[] {
+  const sorted = values.filter(Number.isFinite).sort((a, b) => a - b)
+  if (sorted.length === 0) {
+    return []
+  }
+
+  const min = sorted[0]
+  const max = sorted[sorted.length - 1]
+  const boundaries = [min]
+
+  for (let i = 1; i < count; i++) {
+    const boundary = roundBoundary(sorted[Math.floor((i * sorted.length) / count)])
+    if (boundary > boundaries[boundaries.length - 1] && boundary < max) {
+      boundaries.push(boundary)
+    }
+  }
+  boundaries.push(max)
+
+  return boundaries.slice(1).map((to, i) => ({ from: boundaries[i], to }))
+}
+
+// Like Promise.all over `items.map(fn)`, but with at most `concurrency` calls in flight. Results keep the input order
+export async function mapConcurrent<T, R>(items: T[], concurrency: number, fn: (item: T, index: number) => Promise<R>): Promise<R[]> {
+  if (!Number.isInteger(concurrency) || concurrency < 1) {
+    throw new OramaConfigError('concurrency must be a positive integer')
+  }
+
+  const results = new Array<R>(items.length)
+  let next = 0
+
+  const worker = async () => {
+    while (next < items.length) {
+      const i = next++
+      results[i] = await fn(items[i], i)
+    }
+  }
+
+  await Promise.all(Array.from({ length: Math.min(concurrency, items.length) }, worker))
+  return results
+}
+
+export type AdaptiveConcurrencyOptions = {
+  // Bounds of the concurrency limit, which starts at `min`. Default to 1 and 16
+  min?: number
+  max?: number
+  // Calls slower than this, in milliseconds, count as a latency spike. Defaults to three times the fastest call seen so far
+  latencyThreshold?: number
+}
+
+// AIMD concurrency limit: grows by one every `limit` healthy calls, and halves on a failure or a latency spike
+export class AdaptiveConcurrency {
+  private min: number
+  private max: number
+  private latencyThreshold?: number
+  private window: number
+  private fastest = Infinity
+
+  constructor({ min = 1, max = 16, latencyThreshold }: AdaptiveConcurrencyOptions = {}) {
+    if (!Number.isInteger(min) || !Number.isInteger(max) || min < 1 || max < min) {
+      throw new OramaConfigError('adaptive concurrency bounds must be integers with 1 <= min <= max')
+    }
+
+    this.min = min
+    this.max = max
+    this.latencyThreshold = latencyThreshold
+    this.window = min
+  }
+
+  public get limit(): number {
+    return Math.floor(this.window)
+  }
+
+  public record(ok: boolean, latency: number) {
+    const threshold = this.latencyThreshold ?? this.fastest * 3
+    this.fastest = Math.min(this.fastest, latency)
+
+    if (!ok || latency > threshold) {
+      this.window = Math.max(this.min, this.window / 2)
+    } else {
+      this.window = Math.min(this.max, this.window + 1 / this.limit)
+    }
+  }
+}
+
 export function formatDuration(duration: number): string {
   if (duration < 1000) {
     return `${duration}ms`
@@ -0,0 +1,52 @@
+import type { SearchParams } from './types.ts'
+
+export type ZeroResultsConfig = {
+  // Called for every search that returns no hits, e.g. to forward it to your analytics
+  onZeroResults?: (query: SearchParams) => void
+  // Number of recent searches the rate is computed over. Defaults to 100
+  windowSize?: number
+  // Zero-result rate (0 to 1) above which `onAlert` is called. Defaults to 0.5
+  alertThreshold?: number
+  // Called once when the rate goes above the threshold, and again only after it has dropped back below it
+  onAlert?: (rate: number) => void
+}
+
+export class ZeroResultsMonitor {
+  private outcomes: boolean[] = []
+  private alerting = false
+  private config: ZeroResultsConfig
+
+  constructor(config: ZeroResultsConfig) {
+    this.config = config
+  }
+
+  public getRate(): number {
+    return this.outcomes.length === 0 ? 0 : this.outcomes.filter(Boolean).length / this.outcomes.length
+  }
+
+  public record(query: SearchParams, count: number) {
+    const zeroResults = count === 0
+    if (zeroResults) {
+      this.config.onZeroResults?.(query)
+    }
+
+    const windowSize = this.config.windowSize ?? 100
+    this.outcomes.push(zeroResults)
+    if (this.outcomes.length > windowSize) {
+      this.outcomes.shift()
+    }
+
+    // A handful of early searches says little about the index
+    if (this.outcomes.length < windowSize) {
+      return
+    }
+
+    const rate = this.getRate()
+    const alerting = rate > (this.config.alertThreshold ?? 0.5)
+
+    if (alerting && !this.alerting) {
+      this.config.onAlert?.(rate)
+    }
+    this.alerting = alerting
+  }
+}
@@ -1,12 +1,35 @@
-import type { AnyObject, EmbeddingsModel, Language, Maybe, Nullable } from './lib/types.ts'
-import type { ClientRequestInit } from './common.ts'
+import type { AnyObject, CollectionStats, EmbeddingsModel, Language, Maybe, Nullable } from './lib/types.ts'
+import type { ClientRequestInit, KeyProvider } from './common.ts'
+import type { RequestSigningConfig } from './lib/signing.ts'
+import type { ConfigChange, ConnectionConfig, DeclarativeConfig, ReconcileStatus } from './declarative.ts'
 
 import { Auth, Client } from './common.ts'
-import { createRandomString } from './lib/utils.ts'
+import { createRandomString, globToRegExp, mapConcurrent } from './lib/utils.ts'
+import { Namespace } from './lib/namespace.ts'
+import { OramaConfigError } from './lib/errors.ts'
+import { diffConfig, executeChanges, parseConfig } from './declarative.ts'
+
+const MIN_API_KEY_LENGTH = 8
 
 export type OramaCoreManagerConfig = {
   url: string
   masterAPIKey: string
+  keyProvider?: KeyProvider
+  keyGeneration?: KeyGenerationPolicy
+  signing?: RequestSigningConfig
+  fetch?: typeof fetch
+  // Default timeout for every request, in milliseconds
+  timeout?: number
+  // Transparently prefixes every collection ID, and restricts listing to the collections in the namespace
+  namespace?: string
+}
+
+export type KeyGenerationPolicy = {
+  // Length of the random part, without the prefix. Defaults to 32, and can't be lower than 8
+  length?: number
+  characters?: string
+  readAPIKeyPrefix?: string
+  writeAPIKeyPrefix?: string
 }
 
 export type CreateCollectionParams = {
@@ -46,39 +69,152 @@ export type GetCollectionsResponse = {
   indexes: CollectionIndex[]
 }
 
+export type CleanupCollectionsParams = {
+  // Glob (`ci_*`) or regular expression matched against the collection ID
+  match: string | RegExp
+  // Only delete collections created more than this many milliseconds ago. Collections whose stats can't be read are kept
+  olderThan?: number
+  // Called with the matching collections before anything is deleted. Return false to abort
+  confirm?: (collections: GetCollectionsResponse[]) => boolean | Promise<boolean>
+}
+
+export type CleanupCollectionsResult = {
+  deleted: string[]
+  // A failed delete doesn't stop the others
+  failed: { id: string; error: unknown }[]
+}
+
+export type GetManyCollectionsParams = {
+  // Maximum number of requests in flight. Defaults to 8
+  concurrency?: number
+}
+
+// Catches mistakes before they reach the server, where they would fail with a less helpful message
+function validateCreateCollectionParams(config: CreateCollectionParams) {
+  if (!config.id?.trim()) {
+    throw new OramaConfigError('Collection ID cannot be empty')
+  }
+  if (config.id.includes('/')) {
+    throw new OramaConfigError(`Collection ID "${config.id}" cannot contain "/"`)
+  }
+
+  for (const [name, key] of [['writeAPIKey', config.writeAPIKey], ['readAPIKey', config.readAPIKey]] as const) {
+    if (key != null && key.length < MIN_API_KEY_LENGTH) {
+      throw new OramaConfigError(`${name} must be at least ${MIN_API_KEY_LENGTH} characters long`)
+    }
+  }
+
+  // A shared key would give every reader write access
+  if (config.writeAPIKey != null && config.writeAPIKey === config.readAPIKey) {
+    throw new OramaConfigError('writeAPIKey and readAPIKey must be different')
+  }
+}
+
 export class OramaCoreManager {
   public collection: CollectionNamespace
+  // Holds the signing secret, so keep it out of logs and serialization
+  #connection: ConnectionConfig
 
   constructor(config: OramaCoreManagerConfig) {
+    this.#connection = {
+      url: config.url,
+      signing: config.signing,
+      fetch: config.fetch,
+      namespace: config.namespace,
+    }
+
     const client = new Client({
       auth: new Auth({
         type: 'apiKey',
         apiKey: config.masterAPIKey,
+        keyProvider: config.keyProvider,
         writerURL: config.url,
         readerURL: undefined,
       }),
+      signing: config.signing,
+      fetch: config.fetch,
+      timeout: config.timeout,
     })
 
-    this.collection = new CollectionNamespace(client)
+    this.collection = new CollectionNamespace(
+      client,
+      config.keyGeneration,
+      config.namespace ? new Namespace(config.namespace) : undefined,
+    )
+  }
+
+  // Lists the changes `applyConfig` would make, without applying them. Use `formatConfigPlan` for a readable summary
+  public async planConfig(config: DeclarativeConfig | string): Promise<ConfigChange[]> {
+    return await diffConfig(this, this.#connection, parseConfig(config))
+  }
+
+  // Idempotently brings the server to the desired state and reports the outcome instead of throwing on a failed change,
+  // so it can be called from a control loop (e.g. a Kubernetes operator) until `upToDate` is true
+  public async reconcile(desired: DeclarativeConfig): Promise<ReconcileStatus> {
+    let changes: ConfigChange[]
+
+    try {
+      changes = await diffConfig(this, this.#connection, desired)
+    } catch (error) {
+      return { upToDate: false, applied: [], pending: [], created: {}, failed: { error } }
+    }
+
+    return executeChanges(this, this.#connection, desired, changes)
+  }
+
+  // Creates missing collections and indexes, and sets hooks and system prompts that differ from `config`.
+  // Accepts either a parsed config or its JSON source. Returns the changes that were applied.
+  // Use `reconcile` instead when collections are configured without API keys, to get the generated ones back
+  public async applyConfig(config: DeclarativeConfig | string): Promise<ConfigChange[]> {
+    const status = await this.reconcile(parseConfig(config))
+
+    if (status.failed) {
+      throw status.failed.error
+    }
+
+    return status.applied
   }
 }
 
 class CollectionNamespace {
   client: Client
+  private keyGeneration: KeyGenerationPolicy
+  private namespace?: Namespace
+
+  constructor(client: Client, keyGeneration: KeyGenerationPolicy = {}, namespace?: Namespace) {
+    const { length } = keyGeneration
+    if (length !== undefined && (!Number.isInteger(length) || length < MIN_API_KEY_LENGTH)) {
+      throw new OramaConfigError(`keyGeneration.length must be an integer of at least ${MIN_API_KEY_LENGTH}`)
+    }
 
-  constructor(client: Client) {
     this.client = client
+    this.keyGeneration = keyGeneration
+    this.namespace = namespace
+  }
+
+  private toServerID(collectionID: string): string {
+    return this.namespace ? this.namespace.apply(collectionID) : collectionID
+  }
+
+  private fromServerID(collectionID: string): string {
+    return this.namespace ? this.namespace.strip(collectionID) : collectionID
   }
 
   public async create(
     config: CreateCollectionParams,
     init?: ClientRequestInit,
   ): Promise<NewCollectionResponse> {
+    validateCreateCollectionParams(config)
+
     const body: AnyObject = {
-      id: config.id,
+      id: this.toServerID(config.id),
       description: config.description,
-      write_api_key: config.writeAPIKey ?? createRandomString(32),
-      read_api_key: config.readAPIKey ?? createRandomString(32),
+      write_api_key: config.writeAPIKey ?? this.generateAPIKey('write'),
+      read_api_key: config.readAPIKey ?? this.generateAPIKey('read'),
+    }
+
+    if (config.language) {
+      body.language = config.language
     }
 
     if (config.embeddingsModel) {
@@ -95,31 +231,107 @@ class CollectionNamespace {
     })
 
     return {
-      id: body.id,
+      id: config.id,
       description: body.description,
       writeAPIKey: body.write_api_key,
       readonlyAPIKey: body.read_api_key,
     } as NewCollectionResponse
   }
 
-  public list(init?: ClientRequestInit): Promise<GetCollectionsResponse[]> {
-    return this.client.request<GetCollectionsResponse[]>({
+  private generateAPIKey(type: 'read' | 'write'): string {
+    const { length = 32, characters, readAPIKeyPrefix = '', writeAPIKeyPrefix = '' } = this.keyGeneration
+    const prefix = type === 'read' ? readAPIKeyPrefix : writeAPIKeyPrefix
+
+    return prefix + createRandomString(length, characters)
+  }
+
+  public async list(init?: ClientRequestInit): Promise<GetCollectionsResponse[]> {
+    const collections = await this.client.request<GetCollectionsResponse[]>({
       path: '/v1/collections',
       method: 'GET',
       init,
       apiKeyPosition: 'header',
       target: 'writer',
     })
+
+    if (!this.namespace) {
+      return collections
+    }
+
+    return collections
+      .filter((collection) => this.namespace!.contains(collection.id))
+      .map((collection) => ({ ...collection, id: this.fromServerID(collection.id) }))
   }
 
-  public get(collectionID: string, init?: ClientRequestInit): Promise<GetCollectionsResponse> {
-    return this.client.request<GetCollectionsResponse>({
-      path: `/v1/collections/${collectionID}`,
+  public async get(collectionID: string, init?: ClientRequestInit): Promise<GetCollectionsResponse> {
+    const collection = await this.client.request<GetCollectionsResponse>({
+      path: `/v1/collections/${this.toServerID(collectionID)}`,
       method: 'GET',
       init,
       apiKeyPosition: 'header',
       target: 'writer',
     })
+
+    return { ...collection, id: this.fromServerID(collection.id) }
+  }
+
+  // Fetches several collections concurrently. A collection that can't be fetched maps to its error instead of failing the whole batch
+  public async getMany(
+    collectionIDs: string[],
+    params: GetManyCollectionsParams = {},
+    init?: ClientRequestInit,
+  ): Promise<Map<string, GetCollectionsResponse | Error>> {
+    const results = await mapConcurrent(
+      collectionIDs,
+      params.concurrency ?? 8,
+      (collectionID) => this.get(collectionID, init).catch((error) => (error instanceof Error ? error : new Error(String(error)))),
+    )
+
+    return new Map(collectionIDs.map((collectionID, i) => [collectionID, results[i]]))
+  }
+
+  public async cleanup(params: CleanupCollectionsParams, init?: ClientRequestInit): Promise<CleanupCollectionsResult> {
+    // Without the g and y flags, test() doesn't carry lastIndex over from one collection ID to the next
+    const { match } = params
+    const pattern = typeof match === 'string' ? globToRegExp(match) : new RegExp(match.source, match.flags.replace(/[gy]/g, ''))
+    let matches = (await this.list(init)).filter((collection) => pattern.test(collection.id))
+
+    const { olderThan } = params
+    if (olderThan !== undefined) {
+      const now = Date.now()
+      const stats = await mapConcurrent(matches, 8, (collection) => this.getStats(collection.id, init).catch(() => null))
+
+      matches = matches.filter((_, i) => {
+        const createdAt = stats[i] ? Date.parse(stats[i].created_at) : NaN
+        return !Number.isNaN(createdAt) && now - createdAt > olderThan
+      })
+    }
+
+    const result: CleanupCollectionsResult = { deleted: [], failed: [] }
+    if (matches.length === 0 || (params.confirm && !(await params.confirm(matches)))) {
+      return result
+    }
+
+    for (const collection of matches) {
+      try {
+        await this.delete(collection.id, init)
+        result.deleted.push(collection.id)
+      } catch (error) {
+        result.failed.push({ id: collection.id, error })
+      }
+    }
+
+    return result
+  }
+
+  private getStats(collectionID: string, init?: ClientRequestInit): Promise<CollectionStats> {
+    return this.client.request<CollectionStats>({
+      path: `/v1/collections/${this.toServerID(collectionID)}/stats`,
+      method: 'GET',
+      init,
+      apiKeyPosition: 'query-params',
+      target: 'writer',
+    })
   }
 
   public delete(collectionID: string, init?: ClientRequestInit): Promise<null> {
@@ -127,7 +339,7 @@ class CollectionNamespace {
       path: `/v1/collections/delete`,
       method: 'POST',
       body: {
-        collection_id_to_delete: collectionID,
+        collection_id_to_delete: this.toServerID(collectionID),
       },
       init,
       apiKeyPosition: 'header',
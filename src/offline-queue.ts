@@ -0,0 +1,193 @@
+import type { AnyObject } from './lib/types.ts'
+import type { Index } from './collection.ts'
+
+import { createRandomString, hasLocalStorage } from './lib/utils.ts'
+import { OramaCapacityError, OramaConfigError, OramaError } from './lib/errors.ts'
+
+export type QueuedWrite =
+  | { id: string; queuedAt: number; operation: 'insert' | 'upsert'; documents: AnyObject[] }
+  | { id: string; queuedAt: number; operation: 'delete'; documentIDs: string[] }
+
+export interface OfflineQueueStorage {
+  load(): QueuedWrite[]
+  save(writes: QueuedWrite[]): void
+}
+
+export type OfflineIndexConfig = {
+  maxQueueSize?: number
+  storage?: OfflineQueueStorage
+  // Called when the server rejects a queued write during a drain with a non-retryable error. The write is dropped so it doesn't
+  // block the queue. Retryable failures (network errors, timeouts, 5xx, 408, 429) keep it queued for the next drain
+  onWriteError?: (write: QueuedWrite, error: unknown) => void
+}
+
+export class MemoryOfflineQueueStorage implements OfflineQueueStorage {
+  private writes: QueuedWrite[] = []
+
+  load(): QueuedWrite[] {
+    return this.writes
+  }
+
+  save(writes: QueuedWrite[]): void {
+    this.writes = writes
+  }
+}
+
+export class LocalStorageOfflineQueueStorage implements OfflineQueueStorage {
+  private key: string
+
+  constructor(key: string) {
+    if (!hasLocalStorage) {
+      throw new OramaConfigError('LocalStorageOfflineQueueStorage requires localStorage to be available')
+    }
+
+    this.key = key
+  }
+
+  load(): QueuedWrite[] {
+    const writes = localStorage.getItem(this.key)
+    return writes ? JSON.parse(writes) : []
+  }
+
+  save(writes: QueuedWrite[]): void {
+    localStorage.setItem(this.key, JSON.stringify(writes))
+  }
+}
+
+function isRetryable(error: unknown): boolean {
+  return error instanceof OramaError && error.retryable
+}
+
+export class OfflineIndex {
+  private index: Index
+  private storage: OfflineQueueStorage
+  private maxQueueSize: number
+  private onWriteError?: OfflineIndexConfig['onWriteError']
+  private draining?: Promise<number>
+  private writes: Promise<unknown> = Promise.resolve()
+  private onOnline = () => this.drain()
+
+  constructor(index: Index, config: OfflineIndexConfig = {}) {
+    this.index = index
+    this.storage = config.storage ?? new MemoryOfflineQueueStorage()
+    this.maxQueueSize = config.maxQueueSize ?? 10_000
+    this.onWriteError = config.onWriteError
+
+    if (typeof globalThis.addEventListener === 'function') {
+      globalThis.addEventListener('online', this.onOnline)
+    }
+  }
+
+  // Stops draining the queue when the connection comes back. Queued writes are kept in the storage
+  public dispose(): void {
+    if (typeof globalThis.removeEventListener === 'function') {
+      globalThis.removeEventListener('online', this.onOnline)
+    }
+  }
+
+  public pending(): QueuedWrite[] {
+    return [...this.storage.load()]
+  }
+
+  public insertDocuments(documents: AnyObject | AnyObject[]): Promise<void> {
+    return this.write({
+      id: createRandomString(16),
+      queuedAt: Date.now(),
+      operation: 'insert',
+      documents: Array.isArray(documents) ? documents : [documents],
+    })
+  }
+
+  public upsertDocuments(documents: AnyObject[]): Promise<void> {
+    return this.write({
+      id: createRandomString(16),
+      queuedAt: Date.now(),
+      operation: 'upsert',
+      documents,
+    })
+  }
+
+  public deleteDocuments(documentIDs: string | string[]): Promise<void> {
+    return this.write({
+      id: createRandomString(16),
+      queuedAt: Date.now(),
+      operation: 'delete',
+      documentIDs: Array.isArray(documentIDs) ? documentIDs : [documentIDs],
+    })
+  }
+
+  // Sends queued writes in order and stops at the first one that still can't reach the server
+  public drain(): Promise<number> {
+    if (!this.draining) {
+      this.draining = this.drainQueue().finally(() => {
+        this.draining = undefined
+      })
+    }
+
+    return this.draining
+  }
+
+  private async drainQueue(): Promise<number> {
+    let sent = 0
+
+    while (true) {
+      const [next] = this.storage.load()
+      if (!next) {
+        return sent
+      }
+
+      try {
+        await this.send(next)
+        sent++
+      } catch (error) {
+        if (isRetryable(error)) {
+          return sent
+        }
+        this.onWriteError?.(next, error)
+      }
+
+      // Writes may have been queued while this one was in flight
+      this.storage.save(this.storage.load().filter((write) => write.id !== next.id))
+    }
+  }
+
+  // Writes must reach the server in the order they were made, so each one waits for the previous one to be sent or queued
+  private write(write: QueuedWrite): Promise<void> {
+    const result = this.writes.then(() => this.sendOrQueue(write))
+    this.writes = result.catch(() => {})
+    return result
+  }
+
+  private async sendOrQueue(write: QueuedWrite): Promise<void> {
+    // Anything already queued goes first
+    await this.drain()
+
+    if (this.storage.load().length === 0) {
+      try {
+        return await this.send(write)
+      } catch (error) {
+        if (!isRetryable(error)) {
+          throw error
+        }
+      }
+    }
+
+    const writes = this.storage.load()
+    if (writes.length >= this.maxQueueSize) {
+      throw new OramaCapacityError(`Offline queue is full (${this.maxQueueSize} pending writes)`)
+    }
+
+    this.storage.save([...writes, write])
+  }
+
+  private send(write: QueuedWrite): Promise<void> {
+    switch (write.operation) {
+      case 'insert':
+        return this.index.insertDocuments(write.documents)
+      case 'upsert':
+        return this.index.upsertDocuments(write.documents)
+      case 'delete':
+        return this.index.deleteDocuments(write.documentIDs)
+    }
+  }
+}
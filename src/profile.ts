@@ -1,6 +1,7 @@
 import { createId } from 'npm:@orama/cuid2@2.2.3'
 import { LOCAL_STORAGE_USER_ID_KEY } from './constants.ts'
 import { sendBeacon } from './send-beacon.ts'
+import { OramaConfigError } from './lib/errors.ts'
 
 type ProfileConstructor = {
   endpoint: string
@@ -14,7 +15,7 @@ type ProfileParams = {
 
 export class Profile {
   private readonly endpoint: string
-  private readonly apiKey: string
+  readonly #apiKey: string
 
   private userId: string
   private identity?: string
@@ -23,11 +24,11 @@ export class Profile {
 
   constructor({ endpoint, apiKey }: ProfileConstructor) {
     if (!endpoint || !apiKey) {
-      throw new Error('Endpoint and API Key are required to create a Profile')
+      throw new OramaConfigError('Endpoint and API Key are required to create a Profile')
     }
 
     if (typeof endpoint !== 'string' || typeof apiKey !== 'string') {
-      throw new Error('Endpoint and API Key must be strings')
+      throw new OramaConfigError('Endpoint and API Key must be strings')
     }
 
     if (typeof localStorage !== 'undefined') {
@@ -46,7 +47,7 @@ export class Profile {
     }
 
     this.endpoint = endpoint
-    this.apiKey = apiKey
+    this.#apiKey = apiKey
   }
 
   setParams(params: ProfileParams) {
@@ -73,7 +74,7 @@ export class Profile {
 
   private async sendProfileData(data: Record<string, any>) {
     if (!this.params) {
-      throw new Error('Orama Profile is not initialized')
+      throw new OramaConfigError('Orama Profile is not initialized')
     }
 
     const body = JSON.stringify({
@@ -82,12 +83,12 @@ export class Profile {
       index: this.params.index,
     })
 
-    await sendBeacon(`${this.params?.identifyUrl}?api-key=${this.apiKey}`, body)
+    await sendBeacon(`${this.params?.identifyUrl}?api-key=${this.#apiKey}`, body)
   }
 
   async identify(identity: string) {
     if (typeof identity !== 'string') {
-      throw new Error('Identity must be a string')
+      throw new OramaConfigError('Identity must be a string')
     }
 
     await this.sendProfileData({
@@ -100,7 +101,7 @@ export class Profile {
 
   async alias(alias: string) {
     if (typeof alias !== 'string') {
-      throw new Error('Identity must be a string')
+      throw new OramaConfigError('Identity must be a string')
     }
 
     await this.sendProfileData({
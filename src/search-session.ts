@@ -0,0 +1,68 @@
+import type { AnyObject, SearchParams, SearchResult } from './lib/types.ts'
+import type { ClientRequestInit } from './common.ts'
+import type { CollectionManager } from './collection.ts'
+
+import { OramaConfigError } from './lib/errors.ts'
+
+export type SearchSessionConfig = {
+  // 'exclude' drops documents the session has already shown, 'demote' moves them after the unseen ones
+  mode: 'exclude' | 'demote'
+  // Only the most recently shown documents are remembered, which also bounds how many extra hits 'exclude' asks for.
+  // Defaults to 1000
+  maxSeen?: number
+}
+
+// Keeps track of the documents shown to one user, for discovery feeds that shouldn't repeat themselves
+export class SearchSession {
+  private collection: CollectionManager
+  private mode: SearchSessionConfig['mode']
+  private maxSeen: number
+  // In the order documents were last shown, oldest first
+  private seen = new Set<string>()
+
+  constructor(collection: CollectionManager, config: SearchSessionConfig) {
+    this.collection = collection
+    this.mode = config.mode
+    this.maxSeen = config.maxSeen ?? 1000
+  }
+
+  public async search<R = AnyObject>(query: SearchParams, init?: ClientRequestInit): Promise<SearchResult<R>> {
+    // Pages would shift as documents are excluded, so the session itself is what moves the feed forward
+    if (this.mode === 'exclude' && query.offset) {
+      throw new OramaConfigError("SearchSession doesn't support offset in 'exclude' mode")
+    }
+
+    const limit = query.limit ?? 10
+
+    // Seen documents are removed after the fact, so ask for enough hits to still fill the page
+    const result = await this.collection.search<R>(
+      this.mode === 'exclude' ? { ...query, limit: limit + this.seen.size } : query,
+      init,
+    )
+
+    const unseen = result.hits.filter((hit) => !this.seen.has(hit.id))
+    const hits = this.mode === 'exclude' ? unseen.slice(0, limit) : [...unseen, ...result.hits.filter((hit) => this.seen.has(hit.id))]
+
+    for (const hit of hits) {
+      this.seen.delete(hit.id)
+      this.seen.add(hit.id)
+    }
+
+    for (const documentID of this.seen) {
+      if (this.seen.size <= this.maxSeen) {
+        break
+      }
+      this.seen.delete(documentID)
+    }
+
+    return { ...result, hits }
+  }
+
+  public hasSeen(documentID: string): boolean {
+    return this.seen.has(documentID)
+  }
+
+  public reset() {
+    this.seen.clear()
+  }
+}
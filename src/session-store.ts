@@ -0,0 +1,96 @@
+import type { Nullable } from './lib/types.ts'
+import type { AnswerSessionBranch, Interaction, Message } from './stream-manager.ts'
+
+import { hasLocalStorage } from './lib/utils.ts'
+import { OramaConfigError } from './lib/errors.ts'
+
+export type AnswerSessionSnapshot = {
+  sessionID: string
+  messages: Message[]
+  state: Interaction[]
+  branches: AnswerSessionBranch[]
+}
+
+export interface SessionStore {
+  load(sessionID: string): Promise<Nullable<AnswerSessionSnapshot>>
+  save(snapshot: AnswerSessionSnapshot): Promise<void>
+  delete(sessionID: string): Promise<void>
+}
+
+export class MemorySessionStore implements SessionStore {
+  private sessions = new Map<string, string>()
+
+  load(sessionID: string): Promise<Nullable<AnswerSessionSnapshot>> {
+    const snapshot = this.sessions.get(sessionID)
+    return Promise.resolve(snapshot ? JSON.parse(snapshot) : null)
+  }
+
+  save(snapshot: AnswerSessionSnapshot): Promise<void> {
+    // Store a serialized copy so later mutations of the live session don't leak into the store
+    this.sessions.set(snapshot.sessionID, JSON.stringify(snapshot))
+    return Promise.resolve()
+  }
+
+  delete(sessionID: string): Promise<void> {
+    this.sessions.delete(sessionID)
+    return Promise.resolve()
+  }
+}
+
+export class LocalStorageSessionStore implements SessionStore {
+  private prefix: string
+
+  constructor(prefix = '___orama_answer_session_') {
+    if (!hasLocalStorage) {
+      throw new OramaConfigError('LocalStorageSessionStore requires localStorage to be available')
+    }
+
+    this.prefix = prefix
+  }
+
+  load(sessionID: string): Promise<Nullable<AnswerSessionSnapshot>> {
+    const snapshot = localStorage.getItem(this.prefix + sessionID)
+    return Promise.resolve(snapshot ? JSON.parse(snapshot) : null)
+  }
+
+  save(snapshot: AnswerSessionSnapshot): Promise<void> {
+    localStorage.setItem(this.prefix + snapshot.sessionID, JSON.stringify(snapshot))
+    return Promise.resolve()
+  }
+
+  delete(sessionID: string): Promise<void> {
+    localStorage.removeItem(this.prefix + sessionID)
+    return Promise.resolve()
+  }
+}
+
+// Minimal key-value interface, so any shared storage (Redis, a database table, a file per key) can back a SessionStore
+export interface KeyValueStorage {
+  get(key: string): Nullable<string> | undefined | Promise<Nullable<string> | undefined>
+  set(key: string, value: string): void | Promise<void>
+  delete(key: string): void | Promise<void>
+}
+
+// Persists sessions to shared storage, so every instance of a multi-instance backend can resume a conversation
+export class KeyValueSessionStore implements SessionStore {
+  private storage: KeyValueStorage
+  private prefix: string
+
+  constructor(storage: KeyValueStorage, prefix = 'orama_answer_session:') {
+    this.storage = storage
+    this.prefix = prefix
+  }
+
+  async load(sessionID: string): Promise<Nullable<AnswerSessionSnapshot>> {
+    const snapshot = await this.storage.get(this.prefix + sessionID)
+    return snapshot ? JSON.parse(snapshot) : null
+  }
+
+  async save(snapshot: AnswerSessionSnapshot): Promise<void> {
+    await this.storage.set(this.prefix + snapshot.sessionID, JSON.stringify(snapshot))
+  }
+
+  async delete(sessionID: string): Promise<void> {
+    await this.storage.delete(this.prefix + sessionID)
+  }
+}
@@ -1,13 +1,15 @@
 import type { AdvancedAutoqueryEvent, AnswerEvent } from 'npm:@orama/oramacore-events-parser@0.0.5'
 import type { AnyObject, Nullable, SearchParams, SearchResult } from './index.ts'
 import type { Client, ClientRequestInit } from './common.ts'
+import type { AnswerSessionSnapshot, SessionStore } from './session-store.ts'
 
 import { createId } from 'npm:@orama/cuid2@2.2.3'
 import { parseAnswerStream } from 'npm:@orama/oramacore-events-parser@0.0.5'
-import { hasLocalStorage, isServerRuntime } from './lib/utils.ts'
+import { hasLocalStorage, isServerRuntime, renderPromptTemplate } from './lib/utils.ts'
 import { DEFAULT_SERVER_USER_ID, LOCAL_STORAGE_USER_ID_KEY } from './constants.ts'
 import { safeJSONParse } from './common.ts'
 import { dedupe } from './index.ts'
+import { OramaConfigError, OramaError, OramaParseError } from './lib/errors.ts'
 
 export type AnswerSessionConfig = {
   collectionID: string
@@ -15,6 +17,11 @@ export type AnswerSessionConfig = {
   events?: CreateAISessionConfig['events']
   sessionID?: string
   LLMConfig?: CreateAISessionConfig['LLMConfig']
+  min_similarity?: CreateAISessionConfig['min_similarity']
+  max_documents?: CreateAISessionConfig['max_documents']
+  contentFilter?: CreateAISessionConfig['contentFilter']
+  sessionStore?: CreateAISessionConfig['sessionStore']
+  promptVariables?: CreateAISessionConfig['promptVariables']
   common: Client
 }
 
@@ -49,6 +56,7 @@ export type NLPQueryStep = AdvancedAutoqueryEvent['type']
 
 export type Interaction<D = AnyObject> = {
   id: string
+  parentID: Nullable<string>
   query: string
   optimizedQuery: Nullable<SearchParams>
   response: string
@@ -111,14 +119,36 @@ export type Interaction<D = AnyObject> = {
   }>
 }
 
+export type AnswerSessionBranch = {
+  parentID: Nullable<string>
+  messages: Message[]
+  state: Interaction[]
+}
+
 export type LLMConfig = {
   provider: 'openai' | 'fireworks' | 'together' | 'google'
   model: string
 }
 
+export type ContentFilterContext = {
+  interactionID: string
+  final: boolean
+}
+
+// Return a string to replace (e.g. redact) the response so far, `false` to stop the generation, or nothing to keep it as is
+export type ContentFilter = (response: string, context: ContentFilterContext) => string | false | void
+
 export type CreateAISessionConfig = {
   LLMConfig?: LLMConfig
   initialMessages?: Message[]
+  // Values for `{{variable}}` placeholders in the system messages of `initialMessages`
+  promptVariables?: Record<string, string | number>
+  // Session-wide retrieval defaults, overridable per answer() call
+  min_similarity?: number
+  max_documents?: number
+  contentFilter?: ContentFilter
+  // When set, the session is saved after every answer and can be resumed with `ai.resumeAISession()`
+  sessionStore?: SessionStore
   events?: {
     onStateChange?: (state: Interaction[]) => void
     onEnd?: (state: Interaction[]) => void
@@ -133,10 +163,17 @@ export class OramaCoreStream {
   private events?: CreateAISessionConfig['events']
   private LLMConfig?: CreateAISessionConfig['LLMConfig']
   private sessionID?: string
+  private minSimilarity?: number
+  private maxDocuments?: number
+  private contentFilter?: ContentFilter
+  private sessionStore?: SessionStore
   private lastInteractionParams?: AnswerConfig
+  // Where each interaction's messages start, and the params it was asked with, so it can be edited later
+  private interactions = new Map<string, { messageIndex: number; params: AnswerConfig }>()
 
   public messages: Message[]
   public state: Interaction[] = []
+  public branches: AnswerSessionBranch[] = []
 
   constructor(config: AnswerSessionConfig) {
     this.collectionID = config.collectionID
@@ -144,8 +181,19 @@ export class OramaCoreStream {
 
     this.LLMConfig = config.LLMConfig
     this.messages = config.initialMessages || []
+
+    if (config.promptVariables) {
+      const variables = config.promptVariables
+      this.messages = this.messages.map((message) =>
+        message.role === 'system' ? { ...message, content: renderPromptTemplate(message.content, variables) } : message
+      )
+    }
     this.events = config.events
     this.sessionID = config.sessionID || createId()
+    this.minSimilarity = config.min_similarity
+    this.maxDocuments = config.max_documents
+    this.contentFilter = config.contentFilter
+    this.sessionStore = config.sessionStore
   }
 
   public async answer(data: AnswerConfig, init?: ClientRequestInit): Promise<string> {
@@ -159,6 +207,7 @@ export class OramaCoreStream {
 
   public async *answerStream(data: AnswerConfig, init?: ClientRequestInit): AsyncGenerator<string> {
     this.lastInteractionParams = { ...data }
+    const params = { ...data }
 
     data = this._enrichConfig(data)
 
@@ -168,13 +217,16 @@ export class OramaCoreStream {
     const requestInit = init ?? {}
     requestInit.signal = this.abortController.signal
 
+    const interactionID = data.interactionID || createId()
+    this.interactions.set(interactionID, { messageIndex: this.messages.length, params })
+
     this.messages.push({ role: 'user', content: data.query })
     this.messages.push({ role: 'assistant', content: '' })
-
-    const interactionID = data.interactionID || createId()
+    const parentID = this.state.at(-1)?.id ?? null
 
     this.state.push({
       id: interactionID,
+      parentID,
       query: data.query,
       optimizedQuery: null,
       response: '',
@@ -204,8 +256,8 @@ export class OramaCoreStream {
         messages: this.messages.slice(0, -1), // Send conversation history excluding the empty assistant message
         llm_config: null as Nullable<CreateAISessionConfig['LLMConfig']>,
         related: data.related,
-        min_similarity: data.min_similarity,
-        max_documents: data.max_documents,
+        min_similarity: data.min_similarity ?? this.minSimilarity,
+        max_documents: data.max_documents ?? this.maxDocuments,
         ragat_notation: data.ragat_notation,
       }
 
@@ -223,7 +275,7 @@ export class OramaCoreStream {
       })
 
       if (!reqStream.body) {
-        throw new Error('No response body')
+        throw new OramaParseError(`Response body is empty for answer on collection "${this.collectionID}"`, '')
       }
 
       const emitter = parseAnswerStream(reqStream.body)
@@ -231,8 +283,43 @@ export class OramaCoreStream {
       let finished = false
       let lastYielded = ''
 
+      // `accepted` replaces the response when the filter rejects it, so the rejected text never reaches listeners or the session store
+      const applyContentFilter = (final: boolean, accepted: string) => {
+        if (!this.contentFilter) {
+          return
+        }
+
+        const filtered = this.contentFilter(this.state[currentStateIndex].response, { interactionID, final })
+
+        if (filtered === false) {
+          finished = true
+          this.abortController?.abort()
+          this.state[currentStateIndex].response = accepted
+          this.messages[currentMessageIndex].content = accepted
+          this.state[currentStateIndex].loading = false
+          this.state[currentStateIndex].aborted = true
+          this._pushState()
+          return
+        }
+
+        if (typeof filtered === 'string') {
+          this.state[currentStateIndex].response = filtered
+          this.messages[currentMessageIndex].content = filtered
+        }
+      }
+
       emitter.on('answer_token', (event) => {
+        if (finished) {
+          return
+        }
+
+        const accepted = this.state[currentStateIndex].response
         this.state[currentStateIndex].response += event.token
+        applyContentFilter(false, accepted)
+        if (finished) {
+          return
+        }
+
         this.messages[currentMessageIndex].content = this.state[currentStateIndex].response
         this._pushState()
       })
@@ -322,10 +409,12 @@ export class OramaCoreStream {
           this.state[currentStateIndex].advancedAutoquery!.searchResults = eventData.search_results
 
           const resultsCount = eventData.search_results.reduce(
-            (acc: number, curr: any) => acc + curr.results[0].count,
+            (acc: number, curr: any) => acc + (curr.results?.[0]?.count ?? 0),
             0,
           )
-          const resultText = eventData.search_results.map((x: any) => JSON.parse(x.generated_query).term).join(', ')
+          const resultText = eventData.search_results
+            .map((x: any) => safeJSONParse<{ term?: string }>(x.generated_query)?.term ?? x.generated_query)
+            .join(', ')
           const verboseMessage = `Found ${resultsCount} result${resultsCount === 1 ? '' : 's'} for "${resultText}"`
           const deduped = dedupe(verboseMessage)
           if (deduped) {
@@ -343,7 +432,8 @@ export class OramaCoreStream {
           this._pushState()
         }
 
-        if (event.state === 'completed') {
+        if (event.state === 'completed' && !finished) {
+          applyContentFilter(true, '')
           finished = true
           this.state[currentStateIndex].loading = false
           this._pushState()
@@ -363,6 +453,14 @@ export class OramaCoreStream {
           await new Promise((resolve) => setTimeout(resolve, 0))
         }
       }
+
+      // The final content filter pass may have rewritten the response after its last chunk was yielded
+      const finalResponse = this.state[currentStateIndex].response
+      if (!this.state[currentStateIndex].aborted && finalResponse !== lastYielded) {
+        yield finalResponse
+      }
+
+      await this.sessionStore?.save(this.toSnapshot())
     } catch (error) {
       // Handle AbortError gracefully
       if (error instanceof Error && error.name === 'AbortError') {
@@ -387,21 +485,21 @@ export class OramaCoreStream {
     init?: ClientRequestInit,
   ): string | Promise<string> | AsyncGenerator<string> {
     if (this.state.length === 0 || this.messages.length === 0) {
-      throw new Error('No messages to regenerate')
+      throw new OramaError('No messages to regenerate')
     }
 
     const isLastMessageAssistant = this.messages.at(-1)?.role === 'assistant'
 
     if (!isLastMessageAssistant) {
-      throw new Error('Last message is not an assistant message')
+      throw new OramaError('Last message is not an assistant message')
     }
 
     // Remove the last assistant message and state
     this.messages.pop()
-    this.state.pop()
+    this.interactions.delete(this.state.pop()!.id)
 
     if (!this.lastInteractionParams) {
-      throw new Error('No last interaction parameters available')
+      throw new OramaError('No last interaction parameters available')
     }
 
     if (stream) {
@@ -411,13 +509,74 @@ export class OramaCoreStream {
     return this.answer(this.lastInteractionParams, init)
   }
 
+  public editMessage(
+    interactionID: string,
+    query: string,
+    { stream = true } = {},
+    init?: ClientRequestInit,
+  ): Promise<string> | AsyncGenerator<string> {
+    const index = this.state.findIndex((interaction) => interaction.id === interactionID)
+
+    if (index === -1) {
+      throw new OramaError(`Interaction "${interactionID}" not found`)
+    }
+
+    // Sessions restored from a snapshot don't know where each interaction starts, so assume each one owns a user/assistant pair
+    const interaction = this.interactions.get(interactionID)
+    const messageIndex = interaction?.messageIndex ?? this.messages.length - (this.state.length - index) * 2
+
+    // Keep the replaced conversation tail around so UIs can navigate between branches
+    this.branches.push({
+      parentID: this.state[index].parentID,
+      messages: this.messages.splice(messageIndex),
+      state: this.state.splice(index),
+    })
+
+    for (const replaced of this.branches.at(-1)!.state) {
+      this.interactions.delete(replaced.id)
+    }
+
+    this._pushState()
+
+    const params: AnswerConfig = { ...(interaction?.params ?? this.lastInteractionParams), query, interactionID: undefined }
+
+    if (stream) {
+      return this.answerStream(params, init)
+    }
+
+    return this.answer(params, init)
+  }
+
+  public getSessionID(): string {
+    return this.sessionID!
+  }
+
+  public toSnapshot(): AnswerSessionSnapshot {
+    return {
+      sessionID: this.getSessionID(),
+      messages: this.messages,
+      state: this.state,
+      branches: this.branches,
+    }
+  }
+
+  public restore(snapshot: AnswerSessionSnapshot) {
+    this.sessionID = snapshot.sessionID
+    this.messages = snapshot.messages
+    this.state = snapshot.state
+    this.branches = snapshot.branches ?? []
+    this.interactions.clear()
+
+    this._pushState()
+  }
+
   public abort() {
     if (!this.abortController) {
-      throw new Error('AbortController is not available.')
+      throw new OramaConfigError('AbortController is not available.')
     }
 
     if (this.state.length === 0) {
-      throw new Error('There is no active request to abort.')
+      throw new OramaError('There is no active request to abort.')
     }
 
     this.abortController.abort()
@@ -433,6 +592,8 @@ export class OramaCoreStream {
   public clearSession() {
     this.messages = []
     this.state = []
+    this.branches = []
+    this.interactions.clear()
 
     this._pushState()
   }
@@ -0,0 +1,80 @@
+import type { AnswerConfig, OramaCoreStream } from '../src/stream-manager.ts'
+
+import { assertEquals, assertRejects } from 'jsr:@std/assert'
+import { AnswerSessionPool } from '../src/answer-session-pool.ts'
+import { OramaCapacityError } from '../src/lib/errors.ts'
+
+const tick = () => new Promise((resolve) => setTimeout(resolve, 0))
+
+// Each answer waits until its query is released, so tests decide when generations finish
+function fakeSessions() {
+  const created: string[] = []
+  const started = new Map<string, () => void>()
+
+  const createSession = (sessionID: string) => {
+    created.push(sessionID)
+    return {
+      async *answerStream(data: AnswerConfig) {
+        await new Promise<void>((resolve) => started.set(data.query, resolve))
+        yield `${sessionID}: ${data.query}`
+      },
+    } as unknown as OramaCoreStream
+  }
+
+  return { createSession, created, started }
+}
+
+Deno.test('AnswerSessionPool: caps concurrent generations and hands slots over in order', async () => {
+  const { createSession, started } = fakeSessions()
+  const pool = new AnswerSessionPool({ maxConcurrency: 1 }, createSession)
+
+  const first = pool.answer('a', { query: 'first' })
+  const second = pool.answer('b', { query: 'second' })
+  await tick()
+
+  assertEquals([...started.keys()], ['first'])
+  assertEquals(pool.stats(), { sessions: 2, active: 1, queued: 1 })
+
+  started.get('first')!()
+  assertEquals(await first, 'a: first')
+  await tick()
+
+  assertEquals([...started.keys()], ['first', 'second'])
+  assertEquals(pool.stats(), { sessions: 2, active: 1, queued: 0 })
+
+  started.get('second')!()
+  assertEquals(await second, 'b: second')
+  assertEquals(pool.stats().active, 0)
+})
+
+Deno.test('AnswerSessionPool: rejects requests past the queue size', async () => {
+  const { createSession, started } = fakeSessions()
+  const pool = new AnswerSessionPool({ maxConcurrency: 1, maxQueueSize: 0 }, createSession)
+
+  const first = pool.answer('a', { query: 'first' })
+  await tick()
+
+  const error = await assertRejects(() => pool.answer('b', { query: 'second' }), OramaCapacityError)
+  assertEquals(error.retryable, true)
+
+  started.get('first')!()
+  assertEquals(await first, 'a: first')
+})
+
+Deno.test('AnswerSessionPool: evicts idle sessions but not generating ones', async () => {
+  const { createSession, created, started } = fakeSessions()
+  const pool = new AnswerSessionPool({ maxConcurrency: 2, idleTimeout: 10 }, createSession)
+
+  pool.getSession('idle')
+  const generating = pool.answer('busy', { query: 'slow' })
+  await new Promise((resolve) => setTimeout(resolve, 20))
+
+  assertEquals(pool.evictIdle(), 1)
+  assertEquals(pool.stats().sessions, 1)
+
+  pool.getSession('idle')
+  assertEquals(created, ['idle', 'busy', 'idle'])
+
+  started.get('slow')!()
+  await generating
+})
@@ -0,0 +1,82 @@
+import { assertEquals } from 'jsr:@std/assert'
+import { AnswerSession, MemorySessionStore } from '../src/index.ts'
+import { createClient, stubFetch } from './helpers/fetch.ts'
+
+function answerStreamFetch(tokens: string[]) {
+  const events = [
+    ...tokens.map((token) => ({ type: 'answer_token', token })),
+    { type: 'state_changed', state: 'completed' },
+  ]
+  const body = events.map((event) => `data: ${JSON.stringify(event)}\n\n`).join('')
+
+  return stubFetch(() => new Response(body, { headers: { 'Content-Type': 'text/event-stream' } }))
+}
+
+Deno.test('AnswerSession: yields the response rewritten by the final content filter pass', async () => {
+  const session = new AnswerSession({
+    collectionID: 'collection',
+    common: createClient(answerStreamFetch(['The password ', 'is hunter2']).fetchImpl),
+    contentFilter: (response, { final }) => (final ? response.replace('hunter2', '[redacted]') : undefined),
+  })
+
+  const chunks: string[] = []
+  for await (const chunk of session.answerStream({ query: 'What is the password?' })) {
+    chunks.push(chunk)
+  }
+
+  assertEquals(chunks.at(-1), 'The password is [redacted]')
+  assertEquals(session.state[0].response, 'The password is [redacted]')
+  assertEquals(session.messages.at(-1), { role: 'assistant', content: 'The password is [redacted]' })
+})
+
+Deno.test('AnswerSession: never exposes a chunk rejected by the content filter', async () => {
+  const seen: string[] = []
+  const sessionStore = new MemorySessionStore()
+  const save = sessionStore.save.bind(sessionStore)
+  sessionStore.save = (snapshot) => {
+    seen.push(JSON.stringify(snapshot))
+    return save(snapshot)
+  }
+
+  const session = new AnswerSession({
+    collectionID: 'collection',
+    common: createClient(answerStreamFetch(['The password ', 'is hunter2', ', really']).fetchImpl),
+    contentFilter: (response) => (response.includes('hunter2') ? false : undefined),
+    sessionStore,
+    events: { onStateChange: (state) => seen.push(JSON.stringify(state)) },
+  })
+
+  const chunks: string[] = []
+  for await (const chunk of session.answerStream({ query: 'What is the password?' })) {
+    chunks.push(chunk)
+  }
+
+  assertEquals(chunks.filter((chunk) => chunk.includes('hunter2')), [])
+  assertEquals(session.state[0].response, 'The password ')
+  assertEquals(session.state[0].aborted, true)
+  assertEquals(session.messages.at(-1), { role: 'assistant', content: 'The password ' })
+  assertEquals(seen.filter((entry) => entry.includes('hunter2')), [])
+})
+
+Deno.test('AnswerSession: editMessage replaces the edited interaction and reuses its params', async () => {
+  const { fetchImpl, requests } = answerStreamFetch(['Answer'])
+  const session = new AnswerSession({
+    collectionID: 'collection',
+    common: createClient(fetchImpl),
+    initialMessages: [{ role: 'system', content: 'Be brief' }],
+  })
+
+  await session.answer({ query: 'First', max_documents: 3 })
+  await session.answer({ query: 'Second', max_documents: 7 })
+  await session.editMessage(session.state[0].id, 'First, edited', { stream: false })
+
+  assertEquals(session.messages, [
+    { role: 'system', content: 'Be brief' },
+    { role: 'user', content: 'First, edited' },
+    { role: 'assistant', content: 'Answer' },
+  ])
+  assertEquals(session.state.map((interaction) => interaction.query), ['First, edited'])
+  assertEquals(session.branches[0].messages.map((message) => message.content), ['First', 'Answer', 'Second', 'Answer'])
+  assertEquals(requests.at(-1)!.body.max_documents, 3)
+  assertEquals(requests.at(-1)!.body.messages, [{ role: 'system', content: 'Be brief' }, { role: 'user', content: 'First, edited' }])
+})
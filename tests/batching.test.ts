@@ -0,0 +1,33 @@
+import { assertEquals, assertRejects } from 'jsr:@std/assert'
+import { OramaConfigError } from '../src/lib/errors.ts'
+import { createIndex, stubFetch } from './helpers/fetch.ts'
+
+function batchRecordingIndex() {
+  const { fetchImpl, requests } = stubFetch(() => new Response('null'))
+  return { index: createIndex(fetchImpl), batchSizes: () => requests.map((request) => request.body.length) }
+}
+
+Deno.test('Index: sizes batches by serialized bytes when maxBatchBytes is set', async () => {
+  const { index, batchSizes } = batchRecordingIndex()
+  const documents = [
+    { id: '1', body: 'a'.repeat(40) },
+    { id: '2', body: 'b'.repeat(40) },
+    { id: '3', body: 'c'.repeat(200) },
+    { id: '4', body: 'd' },
+    { id: '5', body: 'e' },
+  ]
+
+  const result = await index.insertDocumentsBatched(documents, { batchSize: 10, maxBatchBytes: 130 })
+
+  // The oversized document goes alone, the small ones still fill batches up to the budget
+  assertEquals(batchSizes(), [2, 1, 2])
+  assertEquals(result, { batches: 3, inserted: 5, failed: [] })
+})
+
+Deno.test('Index: rejects invalid batch options', async () => {
+  const { index } = batchRecordingIndex()
+
+  await assertRejects(() => index.insertDocumentsBatched([], { batchSize: 0 }), OramaConfigError)
+  await assertRejects(() => index.insertDocumentsBatched([], { maxBatchBytes: -1 }), OramaConfigError)
+  await assertRejects(() => index.insertDocumentsBatched([{ id: '1' }], { concurrency: { min: 0 } }), OramaConfigError)
+})
@@ -0,0 +1,285 @@
+import { assertEquals, assertInstanceOf, assertRejects } from 'jsr:@std/assert'
+import { Auth, Client } from '../src/common.ts'
+import { createChaosFetch } from '../src/chaos.ts'
+import { createClient, jsonResponse, type RecordedRequest, stubFetch } from './helpers/fetch.ts'
+import {
+  OramaHTTPError,
+  OramaNetworkError,
+  OramaParseError,
+  OramaRequestError,
+  OramaServerError,
+  OramaTimeoutError,
+} from '../src/lib/errors.ts'
+
+function statusFetch(statuses: number[]) {
+  return stubFetch((_request, i) => jsonResponse({ ok: (statuses[i] ?? 200) === 200 }, statuses[i] ?? 200))
+}
+
+const authorizations = (requests: RecordedRequest[]) => requests.map((request) => request.headers.get('Authorization'))
+
+Deno.test('Client: retries once with a fresh key after a 401', async () => {
+  const { fetchImpl, requests } = statusFetch([401, 200])
+  const client = new Client({
+    auth: new Auth({
+      type: 'apiKey',
+      apiKey: 'old-key',
+      writerURL: 'http://localhost:8080',
+      keyProvider: () => 'new-key',
+    }),
+    fetch: fetchImpl,
+  })
+
+  const result = await client.request({ path: '/v1/collections', method: 'GET', apiKeyPosition: 'header', target: 'writer' })
+
+  assertEquals(result, { ok: true })
+  assertEquals(authorizations(requests), ['Bearer old-key', 'Bearer new-key'])
+})
+
+Deno.test('Client: shares one key refresh between concurrent 401s and discards their bodies', async () => {
+  let cancelled = 0
+  const { fetchImpl } = stubFetch(({ headers }) => {
+    if (headers.get('Authorization') === 'Bearer new-key') {
+      return jsonResponse({ ok: true })
+    }
+    const body = new ReadableStream({ cancel: () => void cancelled++ })
+    return new Response(body, { status: 401 })
+  })
+
+  let refreshes = 0
+  const client = new Client({
+    auth: new Auth({
+      type: 'apiKey',
+      apiKey: 'old-key',
+      writerURL: 'http://localhost:8080',
+      keyProvider: async () => {
+        refreshes++
+        await new Promise((resolve) => setTimeout(resolve, 10))
+        return 'new-key'
+      },
+    }),
+    fetch: fetchImpl,
+  })
+
+  const request = { path: '/v1/collections', method: 'GET', apiKeyPosition: 'header', target: 'writer' } as const
+  assertEquals(await Promise.all([client.request(request), client.request(request)]), [{ ok: true }, { ok: true }])
+  assertEquals(refreshes, 1)
+  assertEquals(cancelled, 2)
+})
+
+Deno.test('Client: surfaces the 401 without a key provider or after the retry', async () => {
+  const withoutProvider = statusFetch([401])
+  const client = createClient(withoutProvider.fetchImpl)
+
+  await assertRejects(() => client.request({ path: '/v1/collections', method: 'GET', apiKeyPosition: 'header', target: 'writer' }))
+  assertEquals(withoutProvider.requests.length, 1)
+
+  const stillRejected = statusFetch([401, 401])
+  const retryingClient = new Client({
+    auth: new Auth({ type: 'apiKey', apiKey: 'key', writerURL: 'http://localhost:8080', keyProvider: () => 'still-wrong' }),
+    fetch: stillRejected.fetchImpl,
+  })
+
+  await assertRejects(() => retryingClient.request({ path: '/v1/collections', method: 'GET', apiKeyPosition: 'header', target: 'writer' }))
+  assertEquals(stillRejected.requests.length, 2)
+})
+
+Deno.test('Auth: caches the JWT until it is about to expire', async () => {
+  const { fetchImpl, requests: jwtRequests } = stubFetch((_request, i) =>
+    jsonResponse({ jwt: `jwt-${i + 1}`, writerURL: 'http://writer', readerApiKey: 'read', readerURL: 'http://reader', expiresIn: 60 })
+  )
+
+  const auth = new Auth({
+    type: 'jwt',
+    authJwtURL: 'http://localhost/jwt',
+    collectionID: 'collection',
+    privateApiKey: 'p_key',
+    fetch: fetchImpl,
+  })
+
+  assertEquals((await auth.getRef('writer')).bearer, 'jwt-1')
+  assertEquals((await auth.getRef('writer')).bearer, 'jwt-1')
+  assertEquals(jwtRequests.length, 1)
+
+  // A 401 drops the cached token so the retry gets a new one
+  assertEquals(await auth.refresh(), true)
+  assertEquals((await auth.getRef('writer')).bearer, 'jwt-2')
+
+  const shortLived = new Auth({
+    type: 'jwt',
+    authJwtURL: 'http://localhost/jwt',
+    collectionID: 'collection',
+    privateApiKey: 'p_key',
+    jwtRefreshMargin: 120_000,
+    fetch: fetchImpl,
+  })
+
+  await shortLived.getRef('writer')
+  await shortLived.getRef('writer')
+  assertEquals(jwtRequests.length, 4)
+})
+
+Deno.test('Auth: shares the JWT fetch across callers without tying it to their signals', async () => {
+  let respond!: () => void
+  const { fetchImpl, requests } = stubFetch(async () => {
+    await new Promise<void>((resolve) => (respond = resolve))
+    return jsonResponse({ jwt: 'jwt-1', writerURL: 'http://writer', readerApiKey: 'read', readerURL: 'http://reader', expiresIn: 60 })
+  })
+  const refreshErrors: unknown[] = []
+  const auth = new Auth({
+    type: 'jwt',
+    authJwtURL: 'http://localhost/jwt',
+    collectionID: 'collection',
+    privateApiKey: 'p_key',
+    onJwtRefreshError: (error) => refreshErrors.push(error),
+    fetch: fetchImpl,
+  })
+
+  const controller = new AbortController()
+  const aborted = auth.getRef('writer', { signal: controller.signal })
+  const other = auth.getRef('writer')
+  await new Promise((resolve) => setTimeout(resolve, 0))
+
+  controller.abort()
+  await assertRejects(() => aborted)
+  respond()
+
+  assertEquals((await other).bearer, 'jwt-1')
+  assertEquals(requests.length, 1)
+  assertEquals(requests[0].signal, undefined)
+  assertEquals(refreshErrors, [])
+})
+
+Deno.test('Auth: reports failed JWT refreshes but not a failed first fetch', async () => {
+  const createAuth = (fetchImpl: typeof fetch, refreshErrors: unknown[]) =>
+    new Auth({
+      type: 'jwt',
+      authJwtURL: 'http://localhost/jwt',
+      collectionID: 'collection',
+      privateApiKey: 'p_key',
+      jwtRefreshMargin: 120_000,
+      onJwtRefreshError: (error) => refreshErrors.push(error),
+      fetch: fetchImpl,
+    })
+
+  const firstFetchErrors: unknown[] = []
+  const unavailable = createAuth(stubFetch(() => jsonResponse('down', 503)).fetchImpl, firstFetchErrors)
+  await assertRejects(() => unavailable.getRef('writer'), OramaServerError)
+  assertEquals(firstFetchErrors, [])
+
+  const refreshErrors: unknown[] = []
+  const { fetchImpl } = stubFetch((_request, i) =>
+    i === 0
+      ? jsonResponse({ jwt: 'jwt-1', writerURL: 'http://writer', readerApiKey: 'read', readerURL: 'http://reader', expiresIn: 60 })
+      : jsonResponse('down', 503)
+  )
+  const auth = createAuth(fetchImpl, refreshErrors)
+
+  assertEquals((await auth.getRef('writer')).bearer, 'jwt-1')
+  // Inside the refresh margin but not expired yet, so the current token is still used
+  assertEquals((await auth.getRef('writer')).bearer, 'jwt-1')
+  assertEquals(refreshErrors.length, 1)
+  assertInstanceOf(refreshErrors[0], OramaServerError)
+})
+
+Deno.test('Client: throws typed errors for HTTP, parse and network failures', async () => {
+  const request = { path: '/v1/collections', method: 'GET', apiKeyPosition: 'header', target: 'writer' } as const
+
+  const httpError = await assertRejects(
+    () => createClient(() => Promise.resolve(new Response('boom', { status: 500 }))).request(request),
+    OramaHTTPError,
+  )
+  assertEquals(httpError.status, 500)
+  assertEquals(httpError.body, 'boom')
+  assertInstanceOf(httpError, OramaServerError)
+  assertEquals(httpError.retryable, true)
+
+  const requestError = await assertRejects(
+    () => createClient(() => Promise.resolve(new Response('missing', { status: 404 }))).request(request),
+    OramaRequestError,
+  )
+  assertEquals(requestError.retryable, false)
+
+  const parseError = await assertRejects(
+    () => createClient(() => Promise.resolve(new Response('not json'))).request(request),
+    OramaParseError,
+  )
+  assertEquals(parseError.body, 'not json')
+
+  const networkError = await assertRejects(
+    () => createClient(() => Promise.reject(new TypeError('connection refused'))).request(request),
+    OramaNetworkError,
+  )
+  assertInstanceOf(networkError.cause, TypeError)
+  assertEquals(networkError.retryable, true)
+})
+
+Deno.test('Client: throws typed HTTP errors for failed streams', async () => {
+  const path = '/v1/collections/collection/generate/answer'
+  const request = { path, method: 'POST', apiKeyPosition: 'query-params', target: 'reader' } as const
+
+  const serverError = await assertRejects(
+    () => createClient(() => Promise.resolve(new Response('unavailable', { status: 503 }))).requestStream(request),
+    OramaServerError,
+  )
+  assertEquals(serverError.status, 503)
+  assertEquals(serverError.body, 'unavailable')
+})
+
+Deno.test('createChaosFetch: injects server errors and malformed bodies', async () => {
+  const baseFetch = stubFetch(() => jsonResponse({ ok: true })).fetchImpl
+  const request = { path: '/v1/collections', method: 'GET', apiKeyPosition: 'header', target: 'writer' } as const
+
+  await assertRejects(() => createClient(createChaosFetch({ serverErrorRate: 1 }, baseFetch)).request(request), OramaServerError)
+  await assertRejects(() => createClient(createChaosFetch({ malformedBodyRate: 1 }, baseFetch)).request(request), OramaParseError)
+  await assertRejects(() => createClient(createChaosFetch({ networkErrorRate: 1 }, baseFetch)).request(request), OramaNetworkError)
+  await assertRejects(
+    () => createClient(createChaosFetch({ timeoutRate: 1, hangTimeout: 10_000 }, baseFetch)).request({ ...request, timeout: 10 }),
+    OramaTimeoutError,
+  )
+
+  assertEquals(await createClient(createChaosFetch({ serverErrorRate: 0.5, random: () => 0.9 }, baseFetch)).request(request), { ok: true })
+})
+
+Deno.test('Client: applies the default timeout unless the request sets its own', async () => {
+  const hangingFetch = stubFetch(({ signal }) =>
+    new Promise((resolve, reject) => {
+      const timeoutID = setTimeout(() => resolve(jsonResponse({ ok: true })), 50)
+      signal?.addEventListener('abort', () => {
+        clearTimeout(timeoutID)
+        reject(signal.reason)
+      })
+    })
+  )
+
+  const client = createClient(hangingFetch.fetchImpl, { timeout: 10 })
+  const request = { path: '/v1/collections', method: 'GET', apiKeyPosition: 'header', target: 'writer' } as const
+
+  const error = await assertRejects(() => client.request(request), OramaTimeoutError)
+  assertEquals(error.retryable, true)
+  assertEquals(await client.request({ ...request, timeout: 1000 }), { ok: true })
+})
+
+Deno.test('Client: keeps the timeout running while the body is read, then unlinks the caller signal', async () => {
+  const stalledBodyFetch = stubFetch(({ signal }) => {
+    const body = new ReadableStream({
+      start(controller) {
+        signal?.addEventListener('abort', () => controller.error(signal.reason))
+      },
+    })
+    return new Response(body)
+  })
+
+  const caller = new AbortController()
+  const removed: string[] = []
+  const removeEventListener = caller.signal.removeEventListener.bind(caller.signal)
+  caller.signal.removeEventListener = ((type: string, listener: EventListener) => {
+    removed.push(type)
+    removeEventListener(type, listener)
+  }) as typeof caller.signal.removeEventListener
+
+  const client = createClient(stalledBodyFetch.fetchImpl, { timeout: 10 })
+  const request = { path: '/v1/collections', method: 'GET', apiKeyPosition: 'header', target: 'writer' } as const
+
+  await assertRejects(() => client.request({ ...request, init: { signal: caller.signal } }), OramaTimeoutError)
+  assertEquals(removed, ['abort'])
+})
@@ -0,0 +1,66 @@
+import { assertEquals, assertRejects } from 'jsr:@std/assert'
+import { OramaConfigError, OramaError } from '../src/lib/errors.ts'
+import { createCollection, createIndex, jsonResponse, stubFetch } from './helpers/fetch.ts'
+
+function searchResponse(documents: Record<string, unknown>[]) {
+  const hits = documents.map((document, i) => ({ id: String(i), score: 1, document }))
+  return jsonResponse({ count: hits.length, hits, facets: {} })
+}
+
+Deno.test('CollectionManager: samples range facets without default params or zero-results tracking', async () => {
+  const { fetchImpl, requests } = stubFetch(() => searchResponse([10, 20, 30, 40].map((price) => ({ price }))))
+  const zeroResults: unknown[] = []
+  const collection = createCollection(fetchImpl, {
+    defaultSearchParams: { where: { inStock: true } },
+    zeroResults: { onZeroResults: (query) => zeroResults.push(query) },
+  })
+
+  const facet = await collection.createRangeFacet('price', { buckets: 2, query: { indexes: ['products'] } })
+
+  assertEquals(facet, { price: { ranges: [{ to: 30 }, { from: 30 }] } })
+  assertEquals(requests[0].body.where, undefined)
+  assertEquals(requests[0].body.indexes, ['products'])
+  assertEquals(zeroResults, [])
+})
+
+Deno.test('Index: count fails with a plain OramaError when the index is missing', async () => {
+  const { fetchImpl } = stubFetch(() => jsonResponse({ document_count: 1, indexes_stats: [{ id: 'other', document_count: 1 }] }))
+
+  const error = await assertRejects(() => createIndex(fetchImpl).count(), OramaError)
+  assertEquals(error instanceof OramaConfigError, false)
+})
+
+Deno.test('Index: applies field policies to inserts and transaction inserts', async () => {
+  const { fetchImpl, requests } = stubFetch(() => jsonResponse(null))
+  const index = createIndex(fetchImpl, {
+    fieldPolicies: { html: { action: 'strip', sourceURLField: 'url' }, summary: { action: 'truncate', maxLength: 3 } },
+  })
+  const document = { id: '1', html: '<html>...</html>', url: 'https://example.com', summary: 'abcdef' }
+  const sent = { id: '1', url: 'https://example.com', source_url: 'https://example.com', summary: 'abc' }
+
+  await index.insertDocuments(document)
+  await index.transaction.insertDocuments([document])
+
+  assertEquals(requests.map((request) => request.body), [[sent], [sent]])
+  assertEquals(requests[1].url.pathname.includes('/indexes/index/'), false)
+})
+
+Deno.test('CollectionManager: normalizes search terms before sending them', async () => {
+  const { fetchImpl, requests } = stubFetch(() => searchResponse([]))
+  const collection = createCollection(fetchImpl, { queryNormalization: { foldDiacritics: true } })
+
+  await collection.search({ term: 'café Crème' })
+
+  assertEquals(requests[0].body.term, 'cafe Creme')
+})
+
+Deno.test('CollectionManager: segments CJK search terms after normalizing them', async () => {
+  const { fetchImpl, requests } = stubFetch(() => searchResponse([]))
+  const collection = createCollection(fetchImpl, { queryNormalization: { form: 'NFKC' }, cjkSegmentation: { locale: 'ja' } })
+
+  await collection.search({ term: '東京都の天気 ｔｏｋｙｏ' })
+
+  const { term } = requests[0].body
+  assertEquals(term.replaceAll(' ', ''), '東京都の天気tokyo')
+  assertEquals(term.split(' ').length > 2, true)
+})
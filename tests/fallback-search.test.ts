@@ -0,0 +1,69 @@
+import { assertEquals, assertRejects } from 'jsr:@std/assert'
+import { FallbackSearcher } from '../src/index.ts'
+import { createCollection, jsonResponse, stubFetch } from './helpers/fetch.ts'
+
+const searchResult = {
+  count: 1,
+  hits: [{ id: '1', score: 1, document: { name: 'John' } }],
+  facets: {},
+  elapsed: { raw: 0, formatted: '0ms' },
+}
+
+function collectionWith(statuses: number[]) {
+  const { fetchImpl, requests } = stubFetch((_request, i) => {
+    const status = statuses[i] ?? 200
+    return status === 200 ? jsonResponse(searchResult) : new Response('unavailable', { status })
+  })
+
+  return { collection: createCollection(fetchImpl), modes: () => requests.map((request) => request.body.mode) }
+}
+
+Deno.test('FallbackSearcher: falls back to a fulltext query', async () => {
+  const { collection, modes } = collectionWith([503, 200])
+  const searcher = new FallbackSearcher(collection, { fallbacks: ['cache', 'fulltext'] })
+
+  const { result, servedBy, errors } = await searcher.search({ term: 'john', mode: 'hybrid' })
+
+  assertEquals(servedBy, 'fulltext')
+  assertEquals(result.count, 1)
+  assertEquals(errors.length, 1)
+  assertEquals(modes(), ['hybrid', 'fulltext'])
+})
+
+Deno.test('FallbackSearcher: serves the last successful result from the cache', async () => {
+  const { collection } = collectionWith([200, 503])
+  const searcher = new FallbackSearcher(collection, { fallbacks: ['cache'] })
+
+  assertEquals((await searcher.search({ term: 'john' })).servedBy, 'primary')
+  assertEquals((await searcher.search({ term: 'john' })).servedBy, 'cache')
+})
+
+Deno.test('FallbackSearcher: uses custom strategies and fails when every path fails', async () => {
+  const { collection } = collectionWith([503, 503, 503])
+  const searcher = new FallbackSearcher(collection, {
+    fallbacks: ['fulltext', { name: 'static', search: () => Promise.resolve({ ...searchResult, count: 0, hits: [] }) }],
+  })
+
+  assertEquals((await searcher.search({ term: 'john' })).servedBy, 'static')
+
+  const failing = new FallbackSearcher(collection, { fallbacks: ['cache'] })
+  await assertRejects(() => failing.search({ term: 'john' }), AggregateError)
+})
+
+Deno.test('FallbackSearcher: skips a fulltext fallback for fulltext queries', async () => {
+  const { collection, modes } = collectionWith([503, 503])
+  const searcher = new FallbackSearcher(collection, { fallbacks: ['fulltext'] })
+
+  await assertRejects(() => searcher.search({ term: 'john', mode: 'fulltext' }), AggregateError)
+  assertEquals(modes(), ['fulltext'])
+})
+
+Deno.test('FallbackSearcher: rethrows aborts without trying the fallbacks', async () => {
+  const { fetchImpl, requests } = stubFetch(() => {
+    throw new DOMException('The search was aborted', 'AbortError')
+  })
+  const searcher = new FallbackSearcher(createCollection(fetchImpl), { fallbacks: ['fulltext'] })
+
+  await assertRejects(() => searcher.search({ term: 'john', mode: 'hybrid' }), DOMException)
+  assertEquals(requests.length, 1)
+})
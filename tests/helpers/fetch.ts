@@ -0,0 +1,57 @@
+import { Auth, Client } from '../../src/common.ts'
+import { CollectionManager, type CollectionManagerConfig, Index, type IndexConfig } from '../../src/collection.ts'
+
+const SERVER_URL = 'http://localhost:8080'
+
+export type RecordedRequest = {
+  url: URL
+  headers: Headers
+  body: any
+  signal?: AbortSignal | null
+}
+
+// Records every request and answers it with `respond`, which also gets the position of the request.
+// Throwing from `respond` rejects the fetch, e.g. with a TypeError to act like an unreachable server
+export function stubFetch(respond: (request: RecordedRequest, i: number) => Response | Promise<Response>) {
+  const requests: RecordedRequest[] = []
+
+  const fetchImpl = (input: URL | RequestInfo, init?: RequestInit) => {
+    const request: RecordedRequest = {
+      url: new URL(input instanceof Request ? input.url : input),
+      headers: new Headers(init?.headers),
+      body: typeof init?.body === 'string' ? JSON.parse(init.body) : undefined,
+      signal: init?.signal,
+    }
+    requests.push(request)
+
+    return Promise.resolve().then(() => respond(request, requests.length - 1))
+  }
+
+  return { fetchImpl: fetchImpl as typeof fetch, requests }
+}
+
+export function jsonResponse(body: unknown, status = 200): Response {
+  return new Response(JSON.stringify(body), { status })
+}
+
+export function createClient(fetchImpl: typeof fetch, { apiKey = 'key', timeout }: { apiKey?: string; timeout?: number } = {}) {
+  return new Client({
+    auth: new Auth({ type: 'apiKey', apiKey, readerURL: SERVER_URL, writerURL: SERVER_URL }),
+    fetch: fetchImpl,
+    timeout,
+  })
+}
+
+export function createCollection(fetchImpl: typeof fetch, config: Partial<CollectionManagerConfig> = {}) {
+  return new CollectionManager({
+    cluster: { readURL: SERVER_URL, writerURL: SERVER_URL },
+    collectionID: 'collection',
+    apiKey: 'read_api_key',
+    fetch: fetchImpl,
+    ...config,
+  })
+}
+
+export function createIndex(fetchImpl: typeof fetch, config?: IndexConfig) {
+  return new Index(createClient(fetchImpl, { apiKey: 'write_api_key' }), 'collection', 'index', config)
+}
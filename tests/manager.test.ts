@@ -0,0 +1,73 @@
+import { assertEquals, assertInstanceOf, assertRejects, assertThrows } from 'jsr:@std/assert'
+import { OramaCoreManager } from '../src/manager.ts'
+import { OramaConfigError, OramaParseError, OramaRequestError } from '../src/lib/errors.ts'
+import { jsonResponse, stubFetch } from './helpers/fetch.ts'
+
+Deno.test('OramaCoreManager: cleanup matches every collection and reports failed deletes', async () => {
+  const collections = ['ci_1', 'ci_2', 'ci_3', 'prod'].map((id) => ({ id, description: null, document_count: 0, indexes: [] }))
+  const createdAt: Record<string, string> = {
+    ci_1: new Date(Date.now() - 60_000).toISOString(),
+    ci_2: new Date(Date.now() - 60_000).toISOString(),
+    ci_3: new Date().toISOString(),
+  }
+
+  const { fetchImpl } = stubFetch(({ url, body }) => {
+    if (url.pathname === '/v1/collections') {
+      return jsonResponse(collections)
+    }
+    if (url.pathname.endsWith('/stats')) {
+      return jsonResponse({ created_at: createdAt[url.pathname.split('/')[3]] })
+    }
+
+    return jsonResponse(null, body.collection_id_to_delete === 'ci_2' ? 409 : 200)
+  })
+
+  const manager = new OramaCoreManager({ url: 'http://localhost:8080', masterAPIKey: 'my-master-api-key', fetch: fetchImpl })
+  const result = await manager.collection.cleanup({ match: /^ci_/g, olderThan: 30_000 })
+
+  assertEquals(result.deleted, ['ci_1'])
+  assertEquals(result.failed.map((failure) => failure.id), ['ci_2'])
+  assertInstanceOf(result.failed[0].error, OramaRequestError)
+})
+
+Deno.test('OramaCoreManager: getMany keeps the order, maps missing collections to errors and limits concurrency', async () => {
+  let inFlight = 0
+  let maxInFlight = 0
+  const { fetchImpl } = stubFetch(async ({ url }) => {
+    inFlight++
+    maxInFlight = Math.max(maxInFlight, inFlight)
+    await new Promise((resolve) => setTimeout(resolve, 5))
+    inFlight--
+
+    const id = url.pathname.split('/').at(-1)!
+    return id === 'missing' ? jsonResponse('not found', 404) : jsonResponse({ id, description: null, document_count: 0, indexes: [] })
+  })
+
+  const manager = new OramaCoreManager({ url: 'http://localhost:8080', masterAPIKey: 'my-master-api-key', fetch: fetchImpl })
+  const collections = await manager.collection.getMany(['c', 'missing', 'a', 'b'], { concurrency: 2 })
+
+  assertEquals([...collections.keys()], ['c', 'missing', 'a', 'b'])
+  assertEquals((collections.get('a') as { id: string }).id, 'a')
+  assertInstanceOf(collections.get('missing'), OramaRequestError)
+  assertEquals(maxInFlight, 2)
+})
+
+Deno.test('OramaCoreManager: rejects malformed and misshaped declarative configs before sending anything', async () => {
+  const { fetchImpl, requests } = stubFetch(() => jsonResponse([]))
+  const manager = new OramaCoreManager({ url: 'http://localhost:8080', masterAPIKey: 'my-master-api-key', fetch: fetchImpl })
+
+  await assertRejects(() => manager.planConfig('{ "collections": ['), OramaParseError)
+  await assertRejects(() => manager.applyConfig('{ "collections": {} }'), OramaConfigError)
+  await assertRejects(() => manager.applyConfig('{ "collections": [{ "description": "no id" }] }'), OramaConfigError)
+  await assertRejects(() => manager.planConfig('null'), OramaConfigError)
+  assertEquals(requests, [])
+})
+
+Deno.test('OramaCoreManager: rejects key generation lengths below the minimum key length', () => {
+  for (const length of [4, 12.5]) {
+    assertThrows(
+      () => new OramaCoreManager({ url: 'http://localhost:8080', masterAPIKey: 'my-master-api-key', keyGeneration: { length } }),
+      OramaConfigError,
+    )
+  }
+})
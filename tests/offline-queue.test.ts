@@ -0,0 +1,89 @@
+import { assertEquals, assertRejects } from 'jsr:@std/assert'
+import { OramaCapacityError } from '../src/lib/errors.ts'
+import { createIndex, stubFetch } from './helpers/fetch.ts'
+
+// Each call takes the next outcome: a status code, or 'offline' to fail like an unreachable server
+function scriptedFetch(outcomes: (number | 'offline')[], delay = 0) {
+  const { fetchImpl, requests } = stubFetch(async (_request, i) => {
+    await new Promise((resolve) => setTimeout(resolve, delay))
+
+    const outcome = outcomes[i] ?? 200
+    if (outcome === 'offline') {
+      throw new TypeError('Failed to fetch')
+    }
+    return new Response('null', { status: outcome })
+  })
+
+  return { fetchImpl, paths: () => requests.map((request) => request.url.pathname.split('/').at(-1)) }
+}
+
+Deno.test('OfflineIndex: queues writes while offline and drains them in order', async () => {
+  const { fetchImpl, paths } = scriptedFetch(['offline', 'offline'])
+  const offline = createIndex(fetchImpl).offline()
+
+  await offline.insertDocuments({ id: '1' })
+  await offline.deleteDocuments('1')
+  assertEquals(offline.pending().map((write) => write.operation), ['insert', 'delete'])
+
+  assertEquals(await offline.drain(), 2)
+  assertEquals(offline.pending(), [])
+  assertEquals(paths(), ['insert', 'insert', 'insert', 'delete'])
+})
+
+Deno.test('OfflineIndex: keeps concurrent writes in call order', async () => {
+  const { fetchImpl, paths } = scriptedFetch(['offline'], 10)
+  const offline = createIndex(fetchImpl).offline()
+
+  await Promise.all([offline.insertDocuments({ id: '1' }), offline.deleteDocuments('1')])
+
+  assertEquals(offline.pending(), [])
+  assertEquals(paths(), ['insert', 'insert', 'delete'])
+})
+
+Deno.test('OfflineIndex: drops writes the server rejects', async () => {
+  const { fetchImpl } = scriptedFetch(['offline', 400])
+  const rejected: string[] = []
+  const offline = createIndex(fetchImpl).offline({ onWriteError: (write) => rejected.push(write.operation) })
+
+  await offline.upsertDocuments([{ id: '1' }])
+  assertEquals(await offline.drain(), 0)
+  assertEquals(rejected, ['upsert'])
+  assertEquals(offline.pending(), [])
+})
+
+Deno.test('OfflineIndex: keeps writes that fail with a retryable server error', async () => {
+  const { fetchImpl, paths } = scriptedFetch(['offline', 503])
+  const rejected: string[] = []
+  const offline = createIndex(fetchImpl).offline({ onWriteError: (write) => rejected.push(write.operation) })
+
+  await offline.insertDocuments({ id: '1' })
+  assertEquals(await offline.drain(), 0)
+  assertEquals(offline.pending().length, 1)
+
+  assertEquals(await offline.drain(), 1)
+  assertEquals(rejected, [])
+  assertEquals(paths(), ['insert', 'insert', 'insert'])
+})
+
+Deno.test('OfflineIndex: rejects writes with a retryable error once the queue is full', async () => {
+  const { fetchImpl } = scriptedFetch(['offline', 'offline'])
+  const offline = createIndex(fetchImpl).offline({ maxQueueSize: 1 })
+
+  await offline.insertDocuments({ id: '1' })
+  const error = await assertRejects(() => offline.insertDocuments({ id: '2' }), OramaCapacityError)
+  assertEquals(error.retryable, true)
+  assertEquals(offline.pending().length, 1)
+})
+
+Deno.test('OfflineIndex: stops draining on reconnection once disposed', async () => {
+  const { fetchImpl, paths } = scriptedFetch(['offline'])
+  const offline = createIndex(fetchImpl).offline()
+
+  await offline.insertDocuments({ id: '1' })
+  offline.dispose()
+  globalThis.dispatchEvent(new Event('online'))
+  await new Promise((resolve) => setTimeout(resolve, 10))
+
+  assertEquals(paths(), ['insert'])
+  assertEquals(offline.pending().length, 1)
+})
@@ -611,3 +611,153 @@ Deno.test('CollectionManager: can update the MCP description', async () => {
   const finalStats = await collectionManager.collections.getStats(id)
   assertEquals(finalStats.mcp_description, finalDescription)
 })
+
+Deno.test('CollectionManager: applies default search params', async () => {
+  const managerWithDefaults = new CollectionManager({
+    cluster: {
+      readURL: 'http://localhost:8080',
+      writerURL: 'http://localhost:8080',
+    },
+    collectionID: id,
+    apiKey: writeAPIKey,
+    defaultSearchParams: {
+      limit: 1,
+    },
+  })
+
+  const result = await managerWithDefaults.search({ term: 'doe', indexes: [indexID] })
+  const overridden = await managerWithDefaults.search({ term: 'doe', indexes: [indexID], limit: 10 })
+
+  assertEquals(result.hits.length, 1)
+  assertEquals(overridden.hits.length, 2)
+})
+
+Deno.test('CollectionManager: inserts documents in batches', async () => {
+  const batchedIndexID = createRandomString(32)
+  await collectionManager.index.create({ id: batchedIndexID })
+
+  const docs = Array.from({ length: 25 }, (_, i) => ({ id: `batched-${i}`, name: `Batched ${i}` }))
+  const result = await collectionManager.index.set(batchedIndexID).insertDocumentsBatched(docs, { batchSize: 10, concurrency: 2 })
+
+  assertEquals(result.batches, 3)
+  assertEquals(result.inserted, 25)
+  assertEquals(result.failed, [])
+})
+
+Deno.test('CollectionManager: audits the collection against a source of truth', async () => {
+  const documents = await collectionManager.collections.getAllDocs(id)
+  const [first, second, ...rest] = documents.map((document) => String(document.id))
+  const hashDocument = (document: Record<string, unknown>) => JSON.stringify(document)
+
+  const report = await collectionManager.collections.audit(
+    [
+      { id: first, hash: JSON.stringify(documents[0]) },
+      { id: second, hash: 'outdated' },
+      { id: 'not-indexed', hash: 'anything' },
+    ],
+    hashDocument,
+  )
+
+  assertEquals(report.missing, ['not-indexed'])
+  assertEquals(report.stale, [second])
+  assertEquals(report.extra.sort(), rest.sort())
+})
+
+Deno.test('CollectionManager: can handle segments and triggers', async () => {
+  const segment = await collectionManager.segments.insert({
+    id: 'evaluator',
+    name: 'Evaluator',
+    description: 'Someone comparing the product with its alternatives',
+    goal: 'Convince them to start a free trial',
+  })
+
+  assertEquals(segment.success, true)
+  assertEquals((await collectionManager.segments.get('evaluator')).segment.name, 'Evaluator')
+
+  const trigger = await collectionManager.triggers.insert({
+    id: 'pricing',
+    name: 'Pricing questions',
+    description: 'The user asks about pricing',
+    response: 'Mention that there is a free tier',
+    segment_id: 'evaluator',
+  })
+
+  assertEquals(trigger.success, true)
+
+  const updated = await collectionManager.triggers.update({ ...trigger.trigger, response: 'Mention the free trial' })
+  assertEquals(updated.trigger.response, 'Mention the free trial')
+  assertEquals((await collectionManager.triggers.getAll()).triggers.length, 1)
+
+  assertEquals((await collectionManager.triggers.delete('pricing')).success, true)
+  assertEquals((await collectionManager.segments.delete('evaluator')).success, true)
+  assertEquals((await collectionManager.segments.getAll()).segments.length, 0)
+})
+
+Deno.test('CollectionManager: inserts documents from an NDJSON stream', async () => {
+  const ndjsonIndexID = createRandomString(32)
+  await collectionManager.index.create({ id: ndjsonIndexID })
+
+  const lines = Array.from({ length: 12 }, (_, i) => JSON.stringify({ id: `ndjson-${i}`, name: `NDJSON ${i}` }) + '\n')
+  async function* source() {
+    yield* lines
+  }
+
+  const result = await collectionManager.index.set(ndjsonIndexID).insertDocumentsFromNDJSON(source(), { batchSize: 5 })
+
+  assertEquals(result.batches, 3)
+  assertEquals(result.inserted, 12)
+  assertEquals(result.failed, [])
+})
+
+Deno.test('CollectionManager: inserts documents from CSV with column mapping', async () => {
+  const csvIndexID = createRandomString(32)
+  await collectionManager.index.create({ id: csvIndexID })
+
+  const csv = [
+    'SKU,Product Name,Price',
+    'csv-1,Keyboard,49.90',
+    'csv-2,"Mouse, wireless",19',
+    'csv-3,Monitor,unknown',
+    'csv-4,Cable',
+  ].join('\n')
+
+  const result = await collectionManager.index.set(csvIndexID).insertDocumentsFromCSV(new Blob([csv]).stream(), {
+    idColumn: 'SKU',
+    columns: { 'Product Name': 'name', Price: 'price' },
+    numericFields: ['price'],
+  })
+
+  assertEquals(result.inserted, 2)
+  assertEquals(result.skipped.map((row) => row.line), [4, 5])
+})
+
+Deno.test('CollectionManager: runs multiple searches at once', async () => {
+  const [john, jane] = await collectionManager.msearch([
+    { term: 'john', indexes: [indexID] },
+    { term: 'jane', indexes: [indexID] },
+  ])
+
+  assertEquals(john.hits.some((hit) => hit.document.name === 'John Doe'), true)
+  assertEquals(jane.hits[0].document.name, 'Jane Doe')
+})
+
+Deno.test('CollectionManager: counts documents in the collection and in an index', async () => {
+  const countIndexID = createRandomString(32)
+  await collectionManager.index.create({ id: countIndexID })
+
+  const index = collectionManager.index.set(countIndexID)
+  const before = await collectionManager.collections.count()
+
+  await index.insertDocuments([{ id: 'count-1' }, { id: 'count-2' }])
+
+  assertEquals(await index.count(), 2)
+  assertEquals(await collectionManager.collections.count(), before + 2)
+})
+
+Deno.test('CollectionManager: creates range facets from sampled documents', async () => {
+  const facet = await collectionManager.createRangeFacet('age', { query: { indexes: [indexID] }, buckets: 2 })
+
+  assertEquals(facet.age.ranges.length > 0, true)
+  assertEquals(facet.age.ranges[0].from, undefined)
+  assertEquals(facet.age.ranges.at(-1)!.to, undefined)
+})
@@ -1,5 +1,5 @@
-import { assertEquals } from 'jsr:@std/assert'
-import { OramaCoreManager } from '../src/index.ts'
+import { assertEquals, assertRejects } from 'jsr:@std/assert'
+import { OramaConfigError, OramaCoreManager } from '../src/index.ts'
 import { createRandomString } from '../src/lib/utils.ts'
 
 const manager = new OramaCoreManager({
@@ -21,6 +21,13 @@ Deno.test('Can create a new collection', async () => {
   assertEquals(newCollection.id, id)
 })
 
+Deno.test('Rejects invalid collection params before sending them', async () => {
+  await assertRejects(() => manager.collection.create({ id: ' ' }), OramaConfigError)
+  await assertRejects(() => manager.collection.create({ id: 'a/b' }), OramaConfigError)
+  await assertRejects(() => manager.collection.create({ id, writeAPIKey: 'short' }), OramaConfigError)
+  await assertRejects(() => manager.collection.create({ id, readAPIKey: writeAPIKey, writeAPIKey }), OramaConfigError)
+})
+
 Deno.test('Can get a collection', async () => {
   const collection = await manager.collection.get(id)
 
@@ -34,3 +41,24 @@ Deno.test('Can list collections', async () => {
   assertEquals(collections.length > 1, true)
   assertEquals(newCollectionExists, id)
 })
+
+Deno.test('Can apply a declarative config idempotently', async () => {
+  const config = {
+    collections: [
+      {
+        id: createRandomString(32),
+        readAPIKey,
+        writeAPIKey,
+        indexes: [{ id: 'products' }],
+      },
+    ],
+  }
+
+  const plan = await manager.planConfig(config)
+  const changes = await manager.applyConfig(config)
+  const secondRun = await manager.applyConfig(JSON.stringify(config))
+
+  assertEquals(plan, changes)
+  assertEquals(changes.map((change) => change.type), ['create_collection', 'create_index'])
+  assertEquals(secondRun, [])
+})
@@ -0,0 +1,108 @@
+import { assertEquals } from 'jsr:@std/assert'
+import { EventsStreamTransformer, type SSEEvent } from '../src/lib/event-stream.ts'
+import { safeJSONParse } from '../src/common.ts'
+import { readCSVRecords, readLines } from '../src/lib/import.ts'
+
+// Fuzz inputs come from a fixed seed, so a failing input can be reproduced by rerunning the test
+const FUZZ_SEED = 0x5eed
+
+// mulberry32: small, fast and good enough to generate test inputs
+function seededRandom(seed: number): () => number {
+  return () => {
+    seed = (seed + 0x6d2b79f5) | 0
+    let t = Math.imul(seed ^ (seed >>> 15), 1 | seed)
+    t = (t + Math.imul(t ^ (t >>> 7), 61 | t)) ^ t
+    return ((t ^ (t >>> 14)) >>> 0) / 4294967296
+  }
+}
+
+async function collectEvents(chunks: Uint8Array[]): Promise<SSEEvent[]> {
+  const stream = new ReadableStream<Uint8Array>({
+    start(controller) {
+      chunks.forEach((chunk) => controller.enqueue(chunk))
+      controller.close()
+    },
+  })
+
+  const events: SSEEvent[] = []
+  for await (const event of stream.pipeThrough(new EventsStreamTransformer())) {
+    events.push(event)
+  }
+
+  return events
+}
+
+Deno.test('EventsStreamTransformer: parses well formed events split across chunks', async () => {
+  const encoder = new TextEncoder()
+  const events = await collectEvents([
+    encoder.encode('data: {"type":"ack"}\n\nda'),
+    encoder.encode('ta: {"type":"done"}\n\n'),
+  ])
+
+  assertEquals(events.map((e) => e.data), ['{"type":"ack"}', '{"type":"done"}'])
+})
+
+Deno.test('EventsStreamTransformer: never throws on malformed input', async () => {
+  const random = seededRandom(FUZZ_SEED)
+
+  for (let i = 0; i < 200; i++) {
+    const chunks = Array.from(
+      { length: 1 + (i % 5) },
+      () => Uint8Array.from({ length: Math.floor(random() * 64) }, () => Math.floor(random() * 256)),
+    )
+
+    await collectEvents(chunks)
+  }
+})
+
+Deno.test('safeJSONParse: returns the raw input on malformed JSON', () => {
+  const random = seededRandom(FUZZ_SEED)
+  const characters = 'abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789'
+  const randomString = (length: number) => Array.from({ length }, () => characters[Math.floor(random() * characters.length)]).join('')
+
+  for (let i = 0; i < 200; i++) {
+    const input = `{${randomString(i % 32)}`
+    assertEquals(safeJSONParse(input), input)
+  }
+
+  assertEquals(safeJSONParse<{ a: number }>('{"a":1}'), { a: 1 })
+})
+
+Deno.test('readLines: splits chunked input on line breaks', async () => {
+  const encoder = new TextEncoder()
+  const stream = new ReadableStream<Uint8Array>({
+    start(controller) {
+      for (const chunk of ['{"id":"1"}\r\n{"id"', ':"2"}\n\n{"id":"3', '"}']) {
+        controller.enqueue(encoder.encode(chunk))
+      }
+      controller.close()
+    },
+  })
+
+  const lines: string[] = []
+  for await (const line of readLines(stream)) {
+    lines.push(line)
+  }
+
+  assertEquals(lines, ['{"id":"1"}', '{"id":"2"}', '', '{"id":"3"}'])
+})
+
+Deno.test('readCSVRecords: handles quotes, escaped quotes and line breaks across chunks', async () => {
+  async function* chunks() {
+    yield 'id,name,notes\r\n1,"Doe, ""J'
+    yield 'ohn""","multi\nline"\n2,Jane,'
+    yield '\n3,"unterminated'
+  }
+
+  const records = []
+  for await (const record of readCSVRecords(chunks())) {
+    records.push(record)
+  }
+
+  assertEquals(records, [
+    { line: 1, fields: ['id', 'name', 'notes'] },
+    { line: 2, fields: ['1', 'Doe, "John"', 'multi\nline'] },
+    { line: 4, fields: ['2', 'Jane', ''] },
+    { line: 5, fields: ['3', 'unterminated'], error: 'Unterminated quoted field' },
+  ])
+})
@@ -0,0 +1,46 @@
+import { assertEquals, assertRejects } from 'jsr:@std/assert'
+import { SearchSession } from '../src/index.ts'
+import { OramaConfigError } from '../src/lib/errors.ts'
+import { createCollection, jsonResponse, stubFetch } from './helpers/fetch.ts'
+
+function collectionReturning(ids: string[]) {
+  const { fetchImpl, requests } = stubFetch(({ body }) => {
+    const hits = ids.slice(0, body.limit).map((id) => ({ id, score: 1, document: {} }))
+    return jsonResponse({ count: ids.length, hits })
+  })
+
+  return { collection: createCollection(fetchImpl), limits: () => requests.map((request) => request.body.limit) }
+}
+
+Deno.test('SearchSession: excludes documents already shown', async () => {
+  const { collection, limits } = collectionReturning(['a', 'b', 'c', 'd', 'e'])
+  const session = new SearchSession(collection, { mode: 'exclude' })
+
+  assertEquals((await session.search({ term: 'x', limit: 2 })).hits.map((hit) => hit.id), ['a', 'b'])
+  assertEquals((await session.search({ term: 'x', limit: 2 })).hits.map((hit) => hit.id), ['c', 'd'])
+  assertEquals(limits(), [2, 4])
+
+  session.reset()
+  assertEquals((await session.search({ term: 'x', limit: 2 })).hits.map((hit) => hit.id), ['a', 'b'])
+})
+
+Deno.test('SearchSession: demotes documents already shown', async () => {
+  const { collection } = collectionReturning(['a', 'b', 'c'])
+  const session = new SearchSession(collection, { mode: 'demote' })
+
+  await session.search({ term: 'x', limit: 1 })
+  assertEquals((await session.search({ term: 'x', limit: 3 })).hits.map((hit) => hit.id), ['b', 'c', 'a'])
+})
+
+Deno.test('SearchSession: forgets the oldest documents past maxSeen and rejects offset when excluding', async () => {
+  const { collection, limits } = collectionReturning(['a', 'b', 'c', 'd', 'e'])
+  const session = new SearchSession(collection, { mode: 'exclude', maxSeen: 2 })
+
+  await session.search({ term: 'x', limit: 2 })
+  assertEquals((await session.search({ term: 'x', limit: 2 })).hits.map((hit) => hit.id), ['c', 'd'])
+  assertEquals([session.hasSeen('a'), session.hasSeen('c')], [false, true])
+  assertEquals((await session.search({ term: 'x', limit: 2 })).hits.map((hit) => hit.id), ['a', 'b'])
+  assertEquals(limits(), [2, 4, 4])
+
+  await assertRejects(() => session.search({ term: 'x', offset: 2 }), OramaConfigError)
+})
@@ -0,0 +1,58 @@
+import { assertEquals } from 'jsr:@std/assert'
+import { AnswerSession, KeyValueSessionStore, MemorySessionStore } from '../src/index.ts'
+import { Auth, Client } from '../src/common.ts'
+
+const client = new Client({
+  auth: new Auth({
+    type: 'apiKey',
+    apiKey: 'read_api_key',
+    readerURL: 'http://localhost:8080',
+  }),
+})
+
+Deno.test('MemorySessionStore: saves and restores answer sessions', async () => {
+  const store = new MemorySessionStore()
+  const session = new AnswerSession({
+    collectionID: 'collection',
+    common: client,
+    sessionID: 'session',
+    initialMessages: [{ role: 'user', content: 'Hello' }],
+  })
+
+  await store.save(session.toSnapshot())
+  session.messages.push({ role: 'assistant', content: 'Not saved' })
+
+  const resumed = new AnswerSession({ collectionID: 'collection', common: client })
+  resumed.restore((await store.load('session'))!)
+
+  assertEquals(resumed.getSessionID(), 'session')
+  assertEquals(resumed.messages, [{ role: 'user', content: 'Hello' }])
+
+  await store.delete('session')
+  assertEquals(await store.load('session'), null)
+})
+
+Deno.test('KeyValueSessionStore: shares sessions through the storage adapter', async () => {
+  const shared = new Map<string, string>()
+  const storage = {
+    get: (key: string) => Promise.resolve(shared.get(key)),
+    set: (key: string, value: string) => void shared.set(key, value),
+    delete: (key: string) => void shared.delete(key),
+  }
+  const session = new AnswerSession({
+    collectionID: 'collection',
+    common: client,
+    sessionID: 'session',
+    initialMessages: [{ role: 'user', content: 'Hello' }],
+  })
+
+  await new KeyValueSessionStore(storage).save(session.toSnapshot())
+  assertEquals([...shared.keys()], ['orama_answer_session:session'])
+
+  // Another instance reading the same storage picks the conversation up
+  const otherInstance = new KeyValueSessionStore(storage)
+  assertEquals((await otherInstance.load('session'))!.messages, [{ role: 'user', content: 'Hello' }])
+
+  await otherInstance.delete('session')
+  assertEquals(await otherInstance.load('session'), null)
+})
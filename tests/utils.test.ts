@@ -0,0 +1,231 @@
+import { assertEquals, assertMatch, assertRejects, assertThrows } from 'jsr:@std/assert'
+import { ConnectivityMonitor } from '../src/lib/connectivity.ts'
+import { OramaConfigError } from '../src/lib/errors.ts'
+import { Namespace } from '../src/lib/namespace.ts'
+import { ZeroResultsMonitor } from '../src/lib/zero-results.ts'
+import { languageFromISOCode, languageToISOCode } from '../src/lib/language.ts'
+import { applyFieldPolicies } from '../src/lib/field-policies.ts'
+import { createSignatureHeaders } from '../src/lib/signing.ts'
+import {
+  AdaptiveConcurrency,
+  createRandomString,
+  createRangeBuckets,
+  createULID,
+  createUUID,
+  globToRegExp,
+  isULID,
+  isUUID,
+  mapConcurrent,
+  normalizeText,
+  renderPromptTemplate,
+  segmentCJK,
+} from '../src/lib/utils.ts'
+
+Deno.test('createRandomString: respects length and character set', () => {
+  assertEquals(createRandomString(48).length, 48)
+  assertMatch(createRandomString(64, 'abc'), /^[abc]{64}$/)
+  assertThrows(() => createRandomString(8, ''))
+})
+
+Deno.test('createUUID and createULID: generate valid, time sortable IDs', () => {
+  assertEquals(isUUID(createUUID()), true)
+  assertEquals(isUUID('not-a-uuid'), false)
+
+  const first = createULID(1_700_000_000_000)
+  const second = createULID(1_700_000_000_001)
+
+  assertEquals(isULID(first), true)
+  assertEquals(isULID('01ARZ3NDEKTSV4RRFFQ69G5FAVX'), false)
+  assertEquals(first.slice(0, 10) < second.slice(0, 10), true)
+  assertThrows(() => createULID(-1))
+})
+
+Deno.test('renderPromptTemplate: resolves variables and rejects missing ones', () => {
+  assertEquals(
+    renderPromptTemplate('Hi {{user_name}}, you are in the {{ segment }} segment', { user_name: 'Ada', segment: 'pro' }),
+    'Hi Ada, you are in the pro segment',
+  )
+  assertThrows(() => renderPromptTemplate('Hi {{user_name}}', {}))
+})
+
+Deno.test('createSignatureHeaders: signs timestamp, method, path and body digest', async () => {
+  const url = new URL('http://localhost:8080/v1/collections/abc/search?api-key=key')
+  const headers = await createSignatureHeaders({ secret: 'secret' }, 'post', url, '{"term":"john"}')
+
+  const encoder = new TextEncoder()
+  const payload = [headers['X-Orama-Timestamp'], 'POST', '/v1/collections/abc/search?api-key=key', headers['X-Orama-Content-SHA256']]
+    .join('\n')
+  const key = await crypto.subtle.importKey('raw', encoder.encode('secret'), { name: 'HMAC', hash: 'SHA-256' }, false, ['verify'])
+  const signature = Uint8Array.from(headers['X-Orama-Signature'].match(/../g)!.map((byte) => parseInt(byte, 16)))
+
+  assertEquals(headers['X-Orama-Content-SHA256'].length, 64)
+  assertEquals(await crypto.subtle.verify('HMAC', key, signature, encoder.encode(payload)), true)
+})
+
+Deno.test('ConnectivityMonitor: reports transitions between states', () => {
+  const monitor = new ConnectivityMonitor(10)
+  const transitions: string[] = []
+  monitor.subscribe((state, previous) => transitions.push(`${previous}->${state}`))
+
+  monitor.record('network-error')
+  monitor.record('success')
+  for (let i = 0; i < 10; i++) {
+    monitor.record('success')
+  }
+
+  assertEquals(transitions, ['online->offline', 'offline->degraded', 'degraded->online'])
+  assertEquals(monitor.getState(), 'online')
+})
+
+Deno.test('ConnectivityMonitor: keeps notifying listeners when one throws', () => {
+  const monitor = new ConnectivityMonitor(10)
+  const states: string[] = []
+  const warn = console.warn
+  console.warn = () => {}
+
+  try {
+    monitor.subscribe(() => {
+      throw new Error('listener failed')
+    })
+    monitor.subscribe((state) => states.push(state))
+    monitor.record('network-error')
+  } finally {
+    console.warn = warn
+  }
+
+  assertEquals(states, ['offline'])
+  assertEquals(monitor.getState(), 'offline')
+})
+
+Deno.test('globToRegExp: matches wildcards and escapes everything else', () => {
+  assertEquals(globToRegExp('ci_*').test('ci_123'), true)
+  assertEquals(globToRegExp('ci_*').test('prod_ci_123'), false)
+  assertEquals(globToRegExp('test.?').test('test.1'), true)
+  assertEquals(globToRegExp('test.?').test('testx1'), false)
+})
+
+Deno.test('Namespace: prefixes and strips collection IDs', () => {
+  const namespace = new Namespace('prod')
+
+  assertEquals(namespace.apply('products'), 'prod__products')
+  assertEquals(namespace.contains('prod__products'), true)
+  assertEquals(namespace.contains('staging__products'), false)
+  assertEquals(namespace.strip('prod__products'), 'products')
+  assertThrows(() => new Namespace(''), OramaConfigError)
+})
+
+Deno.test('mapConcurrent: bounds in-flight calls and keeps input order', async () => {
+  let inFlight = 0
+  let maxInFlight = 0
+
+  const results = await mapConcurrent([5, 1, 4, 2, 3], 2, async (n) => {
+    inFlight++
+    maxInFlight = Math.max(maxInFlight, inFlight)
+    await new Promise((resolve) => setTimeout(resolve, n))
+    inFlight--
+    return n * 10
+  })
+
+  assertEquals(results, [50, 10, 40, 20, 30])
+  assertEquals(maxInFlight, 2)
+  await assertRejects(() => mapConcurrent([1], 0, (n) => Promise.resolve(n)), OramaConfigError)
+})
+
+Deno.test('AdaptiveConcurrency: grows additively and halves on failures and latency spikes', () => {
+  const concurrency = new AdaptiveConcurrency({ min: 1, max: 4, latencyThreshold: 100 })
+  const limits = [true, true, true].map((ok) => {
+    concurrency.record(ok, 10)
+    return concurrency.limit
+  })
+  assertEquals(limits, [2, 2, 3])
+
+  concurrency.record(false, 10)
+  assertEquals(concurrency.limit, 1)
+  concurrency.record(true, 500)
+  assertEquals(concurrency.limit, 1)
+
+  for (let i = 0; i < 20; i++) {
+    concurrency.record(true, 10)
+  }
+  assertEquals(concurrency.limit, 4)
+})
+
+Deno.test('AdaptiveConcurrency: rejects invalid bounds', () => {
+  assertThrows(() => new AdaptiveConcurrency({ min: 0 }), OramaConfigError)
+  assertThrows(() => new AdaptiveConcurrency({ min: 4, max: 2 }), OramaConfigError)
+  assertThrows(() => new AdaptiveConcurrency({ max: 2.5 }), OramaConfigError)
+})
+
+Deno.test('applyFieldPolicies: strips and truncates fields without touching the original', () => {
+  const document = { id: '1', html: '<html>...</html>', url: 'https://example.com', summary: 'abcdef' }
+  const policies = {
+    html: { action: 'strip', sourceURLField: 'url' },
+    summary: { action: 'truncate', maxLength: 3 },
+  } as const
+
+  assertEquals(applyFieldPolicies(document, policies), {
+    id: '1',
+    url: 'https://example.com',
+    source_url: 'https://example.com',
+    summary: 'abc',
+  })
+  assertEquals(document.summary, 'abcdef')
+})
+
+Deno.test('languageFromISOCode: parses ISO 639-1 codes and locale tags', () => {
+  assertEquals(languageFromISOCode('en'), 'english')
+  assertEquals(languageFromISOCode('pt-BR'), 'portuguese')
+  assertEquals(languageFromISOCode('NB_no'), 'norwegian')
+  assertEquals(languageFromISOCode('xx'), undefined)
+  assertEquals(languageFromISOCode('constructor'), undefined)
+  assertEquals(languageToISOCode('japanese'), 'ja')
+})
+
+Deno.test('ZeroResultsMonitor: reports zero-result searches and alerts once per spike', () => {
+  const zeroResultQueries: string[] = []
+  const alerts: number[] = []
+  const monitor = new ZeroResultsMonitor({
+    windowSize: 4,
+    alertThreshold: 0.5,
+    onZeroResults: (query) => zeroResultQueries.push(query.term),
+    onAlert: (rate) => alerts.push(rate),
+  })
+
+  const searches = [['a', 1], ['b', 0], ['c', 0], ['d', 0], ['e', 0], ['f', 3], ['g', 3], ['h', 3], ['i', 0], ['j', 0]] as const
+  for (const [term, count] of searches) {
+    monitor.record({ term }, count)
+  }
+
+  assertEquals(zeroResultQueries, ['b', 'c', 'd', 'e', 'i', 'j'])
+  assertEquals(alerts, [0.75])
+  assertEquals(monitor.getRate(), 0.5)
+})
+
+Deno.test('normalizeText: normalizes forms and folds diacritics of Latin letters only', () => {
+  assertEquals(normalizeText('cafe\u0301'), 'caf\u00e9')
+  assertEquals(normalizeText('Café Ørsted Ελλάδα', { foldDiacritics: true }), 'Cafe Ørsted Ελλαδα')
+  assertEquals(normalizeText('ﬁ', { form: 'NFKC' }), 'fi')
+  // Devanagari vowel signs are combining marks too, but they carry meaning
+  assertEquals(normalizeText('हिंदी', { foldDiacritics: true }), 'हिंदी')
+})
+
+Deno.test('segmentCJK: splits CJK text into words and leaves other text alone', () => {
+  const segmented = segmentCJK('東京都の天気', 'ja')
+
+  assertEquals(segmented.includes(' '), true)
+  assertEquals(segmented.replaceAll(' ', ''), '東京都の天気')
+  assertEquals(segmentCJK('hello world'), 'hello world')
+})
+
+Deno.test('createRangeBuckets: fits rounded ranges to the value distribution', () => {
+  const prices = Array.from({ length: 100 }, (_, i) => (i < 80 ? 5 + i * 0.5 : 100 + (i - 80) * 47))
+
+  assertEquals(createRangeBuckets(prices, 4), [
+    { from: 5, to: 18 },
+    { from: 18, to: 30 },
+    { from: 30, to: 43 },
+    { from: 43, to: 993 },
+  ])
+  assertEquals(createRangeBuckets([7, 7, 7]), [{ from: 7, to: 7 }])
+  assertEquals(createRangeBuckets([]), [])
+})